@@ -1,6 +1,9 @@
 use std::cell::RefCell;
+use std::marker::PhantomData;
 use std::rc::Rc;
+use std::sync::{Arc, Mutex};
 
+use crate::control::motion_profile::{MotionConstraints, MotionProfile};
 use crate::control::pid::{FeedForward, PIDController};
 use crate::core::{Encoder, Motor};
 
@@ -11,15 +14,71 @@ pub trait ElevatorController {
     fn has_reached_target(&self) -> bool;
 }
 
-pub struct ElevatorPIDFFController<'a> {
-    encoder: Rc<RefCell<dyn Encoder + 'a>>,
-    motor: Rc<RefCell<dyn Motor + 'a>>,
+const STOPPED_VELOCITY: f64 = 0.05; // m/s
+const BRAKE_HANDOFF_TIME: f64 = 0.5; // s
+const POWER_SAVE_BALANCE: f64 = 1.0; // V, largest holding voltage of a balanced car
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub enum FloorEstimationPolicy {
+    // nearest floor whenever the car is within the precision band
+    #[default]
+    NearestRound,
+    // like NearestRound, but only once the car has stopped in the band
+    ConservativeStop,
+}
+
+// wider precision band while moving fast, tighter once nearly stopped
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct PrecisionProfile {
+    pub coarse: f64,          // m
+    pub fine: f64,            // m
+    pub speed_threshold: f64, // m/s
+}
+
+// maps an encoder height onto a floor, if it is within `precision` floors of one.
+pub(crate) fn estimate_floor(position: f64, floor_height: f64, precision: f64) -> Option<i8> {
+    let current_floor = position / floor_height;
+    let rounded = current_floor.round();
+
+    if (current_floor - rounded).abs() <= precision {
+        Some(rounded as i8)
+    } else {
+        None
+    }
+}
+
+// hardware on the controller's own thread, and hardware a Send controller can take along
+pub type LocalEncoder<'a> = Rc<RefCell<dyn Encoder + 'a>>;
+pub type LocalMotor<'a> = Rc<RefCell<dyn Motor + 'a>>;
+pub type SharedEncoder<'a> = Arc<Mutex<dyn Encoder + Send + 'a>>;
+pub type SharedMotor<'a> = Arc<Mutex<dyn Motor + Send + 'a>>;
+
+pub struct ElevatorPIDFFController<'a, E = LocalEncoder<'a>, M = LocalMotor<'a>> {
+    encoder: E,
+    motor: M,
     pid: PIDController,
     ff: FeedForward,
     voltage_limit: f64,
     floor_height: f64,
     target_floor: i8,
-    precision: f64,
+    door_open_precision: f64, // m, close enough to report the floor and open the doors
+    releveling_precision: f64, // m, leveled enough to declare the target reached
+    precision_profile: Option<PrecisionProfile>,
+    floor_estimation_policy: FloorEstimationPolicy,
+    power_save_delay: Option<f64>,
+    power_save_balance: f64,
+    settled_time: f64,
+    soft_start_duration: Option<f64>,
+    move_time: f64,
+    brake_engaged: bool,
+    brake_handoff_time: f64,
+    brake_time: f64,
+    saturation_fault_time: Option<f64>,
+    saturated_time: f64,
+    faulted: bool,
+    motion_constraints: Option<MotionConstraints>,
+    motion_profile: Option<MotionProfile>,
+    hardware_lifetime: PhantomData<&'a ()>,
 }
 
 impl<'a> ElevatorPIDFFController<'a> {
@@ -27,6 +86,52 @@ impl<'a> ElevatorPIDFFController<'a> {
         encoder: Rc<RefCell<impl Encoder + 'a>>,
         motor: Rc<RefCell<impl Motor + 'a>>,
         voltage_limit: f64,
+        pid: PIDController,
+        ff: FeedForward,
+        floor_height: f64,
+        precision: f64,
+    ) -> Self {
+        Self::with_hardware(
+            encoder,
+            motor,
+            voltage_limit,
+            pid,
+            ff,
+            floor_height,
+            precision,
+        )
+    }
+}
+
+impl<'a> ElevatorPIDFFController<'a, SharedEncoder<'a>, SharedMotor<'a>> {
+    // for hardware shared with other threads, e.g. a network frontend. the controller
+    // is Send and can itself be moved to another thread
+    pub fn new_shared(
+        encoder: Arc<Mutex<impl Encoder + Send + 'a>>,
+        motor: Arc<Mutex<impl Motor + Send + 'a>>,
+        voltage_limit: f64,
+        pid: PIDController,
+        ff: FeedForward,
+        floor_height: f64,
+        precision: f64,
+    ) -> Self {
+        Self::with_hardware(
+            encoder,
+            motor,
+            voltage_limit,
+            pid,
+            ff,
+            floor_height,
+            precision,
+        )
+    }
+}
+
+impl<E: Encoder, M: Motor> ElevatorPIDFFController<'_, E, M> {
+    fn with_hardware(
+        encoder: E,
+        motor: M,
+        voltage_limit: f64,
         mut pid: PIDController,
         ff: FeedForward,
         floor_height: f64,
@@ -41,54 +146,689 @@ impl<'a> ElevatorPIDFFController<'a> {
             ff,
             voltage_limit,
             floor_height,
-            precision,
+            door_open_precision: precision,
+            releveling_precision: precision,
             target_floor: 0,
+            precision_profile: None,
+            floor_estimation_policy: FloorEstimationPolicy::default(),
+            power_save_delay: None,
+            power_save_balance: POWER_SAVE_BALANCE,
+            settled_time: 0.0,
+            soft_start_duration: None,
+            move_time: 0.0,
+            brake_engaged: false,
+            brake_handoff_time: BRAKE_HANDOFF_TIME,
+            brake_time: 0.0,
+            saturation_fault_time: None,
+            saturated_time: 0.0,
+            faulted: false,
+            motion_constraints: None,
+            motion_profile: None,
+            hardware_lifetime: PhantomData,
         }
     }
 
+    // de-energize the motor once the car has been leveled and still for `delay` seconds.
+    // only a balanced car is powered down, an unbalanced one would sag
+    pub fn set_power_save_delay(&mut self, delay: Option<f64>) {
+        self.power_save_delay = delay;
+        self.settled_time = 0.0;
+    }
+
+    // the counterweight balances the car when its holding voltage is within `voltage`
+    pub fn set_power_save_balance(&mut self, voltage: f64) {
+        self.power_save_balance = voltage;
+    }
+
+    fn is_balanced(&self) -> bool {
+        self.ff.kg.abs() <= self.power_save_balance
+    }
+
+    pub fn is_powered_down(&self) -> bool {
+        self.power_save_delay
+            .is_some_and(|delay| self.settled_time >= delay)
+            && self.is_balanced()
+    }
+
+    // while the brake is engaged the motor voltage ramps to zero over the handoff time,
+    // releasing it gives the motor full control again, with the profile restarted from
+    // wherever the brake held the car
+    pub fn set_brake_engaged(&mut self, engaged: bool) {
+        if self.brake_engaged != engaged {
+            self.brake_engaged = engaged;
+            self.brake_time = 0.0;
+            self.pid.reset();
+            if !engaged {
+                self.motion_profile = None;
+            }
+        }
+    }
+
+    pub fn is_brake_engaged(&self) -> bool {
+        self.brake_engaged
+    }
+
+    pub fn set_brake_handoff_time(&mut self, handoff_time: f64) {
+        self.brake_handoff_time = handoff_time;
+    }
+
+    fn get_brake_handoff_scale(&self) -> f64 {
+        match self.brake_engaged {
+            true if self.brake_handoff_time > 0.0 => {
+                (1.0 - self.brake_time / self.brake_handoff_time).max(0.0)
+            }
+            true => 0.0,
+            false => 1.0,
+        }
+    }
+
+    // e.g. a recalibrated kg after a payload change, the PID limits follow so that
+    // pid + kg stays within the voltage limit
+    pub fn set_feedforward(&mut self, ff: FeedForward) {
+        self.pid
+            .set_output_limits(-self.voltage_limit - ff.kg, self.voltage_limit - ff.kg);
+        self.ff = ff;
+    }
+
+    // a non-finite output always faults, saturation only after `time` seconds when set
+    pub fn set_saturation_fault_time(&mut self, time: Option<f64>) {
+        self.saturation_fault_time = time;
+        self.saturated_time = 0.0;
+    }
+
+    pub fn is_faulted(&self) -> bool {
+        self.faulted
+    }
+
+    // the profile kept running while faulted, it restarts from the car's position
+    pub fn clear_fault(&mut self) {
+        self.faulted = false;
+        self.saturated_time = 0.0;
+        self.pid.reset();
+        self.motion_profile = None;
+    }
+
+    // a faulted controller only balances gravity and lets the car drift
+    fn get_fault_voltage(&self) -> f64 {
+        match self.ff.kg.is_finite() {
+            true => self.ff.kg.clamp(-self.voltage_limit, self.voltage_limit),
+            false => 0.0,
+        }
+    }
+
+    fn detect_fault(&mut self, voltage: f64, dt: f64) {
+        if !voltage.is_finite() {
+            log::error!("controller output is not finite: {}", voltage);
+            self.faulted = true;
+            return;
+        }
+
+        if voltage.abs() >= self.voltage_limit {
+            self.saturated_time += dt;
+        } else {
+            self.saturated_time = 0.0;
+        }
+
+        if let Some(limit) = self.saturation_fault_time
+            && self.saturated_time >= limit
+        {
+            log::error!("controller saturated for {:.2}s", self.saturated_time);
+            self.faulted = true;
+        }
+    }
+
+    // ramp the PID authority from zero to full over `duration` seconds at the start of a move,
+    // the gravity feedforward is applied in full throughout
+    pub fn set_soft_start(&mut self, duration: Option<f64>) {
+        self.soft_start_duration = duration;
+    }
+
+    fn get_soft_start_scale(&self) -> f64 {
+        match self.soft_start_duration {
+            Some(duration) if duration > 0.0 => (self.move_time / duration).min(1.0),
+            _ => 1.0,
+        }
+    }
+
+    // the PID follows a trapezoidal setpoint towards the target instead of a step, and
+    // kv and ka feed forward the planned velocity and acceleration
+    pub fn set_motion_profile(&mut self, constraints: Option<MotionConstraints>) {
+        self.motion_constraints = constraints;
+        self.motion_profile = None;
+    }
+
+    // the height the PID is steering to on this tick, the target itself without a profile
+    pub fn get_setpoint_height(&self) -> f64 {
+        match self.motion_profile {
+            Some(profile) => profile.get_position(),
+            None => self.get_target_height(),
+        }
+    }
+
+    pub fn get_setpoint_velocity(&self) -> f64 {
+        self.motion_profile
+            .map_or(0.0, |profile| profile.get_velocity())
+    }
+
+    // advances the profile, starting it from the car's position on the first tick
+    fn update_motion_profile(&mut self, current_pos: f64, target_pos: f64, dt: f64) {
+        if let Some(constraints) = self.motion_constraints {
+            self.motion_profile
+                .get_or_insert(MotionProfile::new(current_pos))
+                .step(target_pos, constraints, dt);
+        }
+    }
+
+    fn get_feedforward_voltage(&self) -> f64 {
+        match self.motion_profile {
+            Some(profile) => {
+                self.ff.kg
+                    + self.ff.kv * profile.get_velocity()
+                    + self.ff.ka * profile.get_acceleration()
+            }
+            None => self.ff.kg,
+        }
+    }
+
+    pub fn set_floor_estimation_policy(&mut self, policy: FloorEstimationPolicy) {
+        self.floor_estimation_policy = policy;
+    }
+
+    pub fn set_precision_profile(&mut self, coarse: f64, fine: f64, speed_threshold: f64) {
+        self.precision_profile = Some(PrecisionProfile {
+            coarse,
+            fine,
+            speed_threshold,
+        });
+    }
+
+    pub fn set_door_open_precision(&mut self, precision: f64) {
+        self.door_open_precision = precision;
+    }
+
+    pub fn set_releveling_precision(&mut self, precision: f64) {
+        self.releveling_precision = precision;
+    }
+
+    // a precision profile, when set, replaces both fixed bands
+    fn get_releveling_precision(&self) -> f64 {
+        match self.precision_profile {
+            Some(_) => self.get_effective_precision(),
+            None => self.releveling_precision,
+        }
+    }
+
+    pub fn can_open_doors(&self) -> bool {
+        self.get_current_floor() == Some(self.target_floor)
+    }
+
+    pub fn get_effective_precision(&self) -> f64 {
+        match self.precision_profile {
+            Some(profile) if self.encoder.get_velocity().abs() >= profile.speed_threshold => {
+                profile.coarse
+            }
+            Some(profile) => profile.fine,
+            None => self.door_open_precision,
+        }
+    }
+
+    pub fn get_floor_height(&self) -> f64 {
+        self.floor_height
+    }
+
     pub fn get_target_height(&self) -> f64 {
         self.target_floor as f64 * self.floor_height
     }
 
     pub fn get_current_height(&self) -> f64 {
-        self.encoder.borrow().get_position()
+        self.encoder.get_position()
     }
 }
 
-impl ElevatorController for ElevatorPIDFFController<'_> {
+impl<E: Encoder, M: Motor> ElevatorController for ElevatorPIDFFController<'_, E, M> {
     fn set_target_floor(&mut self, floor: i8) {
         if self.target_floor != floor {
             self.target_floor = floor;
             self.pid.reset();
+            self.settled_time = 0.0;
+            self.move_time = 0.0;
         }
     }
 
     fn tick(&mut self, dt: f64) {
-        let current_pos = self.encoder.borrow().get_position();
+        if self.power_save_delay.is_some() {
+            let is_still = self.encoder.get_velocity().abs() < STOPPED_VELOCITY;
+
+            if self.has_reached_target() && is_still {
+                self.settled_time += dt;
+            } else {
+                self.settled_time = 0.0;
+            }
+
+            if self.is_powered_down() {
+                self.motor.set_voltage(0.0);
+                return;
+            }
+        }
+
+        let current_pos = self.encoder.get_position();
         let target_pos = self.target_floor as f64 * self.floor_height;
-        let error = target_pos - current_pos;
+        self.update_motion_profile(current_pos, target_pos, dt);
+        let error = self.get_setpoint_height() - current_pos;
+
+        self.move_time += dt;
+        if self.brake_engaged {
+            self.brake_time += dt;
+        }
+
+        let voltage = (self.pid.update(error, dt) * self.get_soft_start_scale()
+            + self.get_feedforward_voltage())
+            * self.get_brake_handoff_scale();
+
+        if !self.faulted {
+            self.detect_fault(voltage, dt);
+        }
 
-        let voltage = self.pid.update(error, dt) + self.ff.kg;
-        self.motor
-            .borrow_mut()
-            .set_voltage(voltage.clamp(-self.voltage_limit, self.voltage_limit));
+        let voltage = match self.faulted {
+            true => self.get_fault_voltage(),
+            false => voltage.clamp(-self.voltage_limit, self.voltage_limit),
+        };
+        self.motor.set_voltage(voltage);
     }
 
     fn get_current_floor(&self) -> Option<i8> {
-        let current_floor = self.encoder.borrow().get_position() / self.floor_height;
-        let rounded = current_floor.round();
+        let encoder = &self.encoder;
 
-        if (current_floor - rounded).abs() <= self.precision {
-            Some(rounded as i8)
-        } else {
-            None
+        if self.floor_estimation_policy == FloorEstimationPolicy::ConservativeStop
+            && encoder.get_velocity().abs() >= STOPPED_VELOCITY
+        {
+            return None;
         }
+
+        estimate_floor(
+            encoder.get_position(),
+            self.floor_height,
+            self.get_effective_precision() / self.floor_height,
+        )
     }
 
     fn has_reached_target(&self) -> bool {
-        let current = self.encoder.borrow().get_position();
+        let current = self.encoder.get_position();
         let target = self.target_floor as f64 * self.floor_height;
 
-        (current - target).abs() < self.precision
+        (current - target).abs() < self.get_releveling_precision()
+    }
+}
+
+#[cfg(test)]
+mod elevator_control_tests {
+    use super::*;
+    use crate::{ElevatorPhysics, SimulatedEncoder, SimulatedMotor};
+
+    fn build_controller<'a>(
+        encoder: &Rc<RefCell<SimulatedEncoder>>,
+        motor: &Rc<RefCell<SimulatedMotor>>,
+    ) -> ElevatorPIDFFController<'a> {
+        ElevatorPIDFFController::new(
+            Rc::clone(encoder),
+            Rc::clone(motor),
+            12.0,
+            PIDController::new(5.0, 0.0, 20.0),
+            FeedForward::new(10.0, 0.0, 0.0),
+            5.0,
+            0.1,
+        )
+    }
+
+    #[test]
+    fn test_shared_hardware_driven_from_thread() {
+        let encoder = Arc::new(Mutex::new(SimulatedEncoder::new(0.0)));
+        let motor = Arc::new(Mutex::new(SimulatedMotor::new()));
+
+        fn assert_send<T: Send>(_: &T) {}
+
+        // built here, then moved to the thread that runs it
+        let mut controller = ElevatorPIDFFController::new_shared(
+            Arc::clone(&encoder),
+            Arc::clone(&motor),
+            12.0,
+            PIDController::new(5.0, 0.0, 20.0),
+            FeedForward::new(10.0, 0.0, 0.0),
+            5.0,
+            0.1,
+        );
+        assert_send(&controller);
+        let handle = std::thread::spawn(move || {
+            controller.set_target_floor(1);
+            controller.tick(1.0 / 60.0);
+        });
+        handle.join().unwrap();
+
+        assert!(
+            motor.lock().unwrap().get_voltage() > 0.0,
+            "controller thread should drive the shared motor up"
+        );
+
+        encoder.lock().unwrap().set_position(5.0);
+        let reader = {
+            let encoder = Arc::clone(&encoder);
+            std::thread::spawn(move || encoder.get_position())
+        };
+        assert_eq!(reader.join().unwrap(), 5.0);
+    }
+
+    #[test]
+    fn test_feedforward_update_respects_voltage_limit() {
+        let encoder = Rc::new(RefCell::new(SimulatedEncoder::new(0.0)));
+        let motor = Rc::new(RefCell::new(SimulatedMotor::new()));
+        let mut controller = build_controller(&encoder, &motor);
+        controller.set_target_floor(3);
+
+        controller.set_feedforward(FeedForward::new(4.0, 0.0, 0.0));
+        for _ in 0..120 {
+            controller.tick(1.0 / 60.0);
+            assert!(motor.borrow().get_voltage() <= 12.0);
+        }
+        assert_eq!(
+            motor.borrow().get_voltage(),
+            12.0,
+            "a far target should still get the full voltage"
+        );
+
+        controller.set_target_floor(-3);
+        controller.set_feedforward(FeedForward::new(11.0, 0.0, 0.0));
+        for _ in 0..120 {
+            controller.tick(1.0 / 60.0);
+            assert!(motor.borrow().get_voltage().abs() <= 12.0);
+        }
+        assert_eq!(motor.borrow().get_voltage(), -12.0);
+    }
+
+    #[test]
+    fn test_fault_on_non_finite_output() {
+        let encoder = Rc::new(RefCell::new(SimulatedEncoder::new(0.0)));
+        let motor = Rc::new(RefCell::new(SimulatedMotor::new()));
+        let mut controller = build_controller(&encoder, &motor);
+        controller.set_target_floor(1);
+
+        controller.tick(1.0 / 60.0);
+        assert!(!controller.is_faulted());
+
+        encoder.borrow_mut().set_position(f64::NAN);
+        controller.tick(1.0 / 60.0);
+        assert!(controller.is_faulted(), "NaN output should fault");
+        assert_eq!(
+            motor.borrow().get_voltage(),
+            10.0,
+            "faulted controller only holds gravity"
+        );
+
+        // stays faulted after the reading recovers
+        encoder.borrow_mut().set_position(0.0);
+        controller.tick(1.0 / 60.0);
+        assert!(controller.is_faulted());
+
+        controller.clear_fault();
+        controller.set_saturation_fault_time(Some(0.5));
+        for _ in 0..60 {
+            controller.tick(1.0 / 60.0);
+        }
+        assert!(
+            controller.is_faulted(),
+            "a second at the voltage limit should fault"
+        );
+        assert!(motor.borrow().get_voltage().is_finite());
+    }
+
+    #[test]
+    fn test_floor_estimation_policies() {
+        let encoder = Rc::new(RefCell::new(SimulatedEncoder::new(0.0)));
+        let motor = Rc::new(RefCell::new(SimulatedMotor::new()));
+        let mut controller = build_controller(&encoder, &motor);
+
+        // just below floor 3, still moving up
+        encoder.borrow_mut().set_position(14.95);
+        encoder.borrow_mut().set_velocity(0.5);
+
+        assert_eq!(controller.get_current_floor(), Some(3));
+        controller.set_floor_estimation_policy(FloorEstimationPolicy::ConservativeStop);
+        assert_eq!(
+            controller.get_current_floor(),
+            None,
+            "conservative policy waits for the car to stop"
+        );
+
+        encoder.borrow_mut().set_velocity(0.0);
+        assert_eq!(controller.get_current_floor(), Some(3));
+    }
+
+    #[test]
+    fn test_precision_profile_follows_speed() {
+        let encoder = Rc::new(RefCell::new(SimulatedEncoder::new(0.0)));
+        let motor = Rc::new(RefCell::new(SimulatedMotor::new()));
+        let mut controller = build_controller(&encoder, &motor);
+        controller.set_precision_profile(1.5, 0.1, 0.5);
+        controller.set_target_floor(3);
+
+        // 0.2m / 0.04 floors short of floor 3
+        encoder.borrow_mut().set_position(14.8);
+
+        encoder.borrow_mut().set_velocity(1.2);
+        assert_eq!(controller.get_effective_precision(), 1.5);
+        assert_eq!(controller.get_current_floor(), Some(3));
+        assert!(controller.has_reached_target(), "coarse band at speed");
+
+        encoder.borrow_mut().set_velocity(0.0);
+        assert_eq!(controller.get_effective_precision(), 0.1);
+        assert_eq!(controller.get_current_floor(), None, "fine band at rest");
+        assert!(!controller.has_reached_target());
+
+        // 1m / 0.2 floors off: only the coarse band accepts it
+        encoder.borrow_mut().set_position(14.0);
+        assert_eq!(controller.get_current_floor(), None);
+        encoder.borrow_mut().set_velocity(-1.2);
+        assert_eq!(controller.get_current_floor(), Some(3));
+    }
+
+    #[test]
+    fn test_power_save_cuts_voltage_when_settled() {
+        let encoder = Rc::new(RefCell::new(SimulatedEncoder::new(5.0)));
+        let motor = Rc::new(RefCell::new(SimulatedMotor::new()));
+        let mut controller = build_controller(&encoder, &motor);
+        controller.set_feedforward(FeedForward::new(0.5, 0.0, 0.0));
+        controller.set_power_save_delay(Some(2.0));
+        controller.set_target_floor(1);
+
+        let dt = 0.1;
+        controller.tick(dt);
+        assert!(
+            motor.borrow().get_voltage() > 0.0,
+            "holding voltage applied"
+        );
+
+        for _ in 0..15 {
+            controller.tick(dt);
+        }
+        assert!(!controller.is_powered_down());
+        assert!(motor.borrow().get_voltage() > 0.0);
+
+        for _ in 0..5 {
+            controller.tick(dt);
+        }
+        assert!(controller.is_powered_down());
+        assert_eq!(motor.borrow().get_voltage(), 0.0);
+
+        // next move re-energizes the motor
+        controller.set_target_floor(2);
+        controller.tick(dt);
+        assert!(!controller.is_powered_down());
+        assert!(motor.borrow().get_voltage() > 0.0);
+    }
+
+    #[test]
+    fn test_power_save_needs_a_balanced_car() {
+        let encoder = Rc::new(RefCell::new(SimulatedEncoder::new(5.0)));
+        let motor = Rc::new(RefCell::new(SimulatedMotor::new()));
+        let mut controller = build_controller(&encoder, &motor);
+        controller.set_power_save_delay(Some(1.0));
+        controller.set_target_floor(1);
+
+        for _ in 0..20 {
+            controller.tick(0.1);
+        }
+        assert!(
+            !controller.is_powered_down(),
+            "10V of holding voltage is not balanced"
+        );
+        assert!(motor.borrow().get_voltage() > 0.0);
+
+        controller.set_power_save_balance(10.0);
+        controller.tick(0.1);
+        assert!(controller.is_powered_down());
+        assert_eq!(motor.borrow().get_voltage(), 0.0);
+    }
+
+    #[test]
+    fn test_motion_profile_drives_the_car_to_target() {
+        let encoder = Rc::new(RefCell::new(SimulatedEncoder::new(0.0)));
+        let motor = Rc::new(RefCell::new(SimulatedMotor::new()));
+        let mut physics = ElevatorPhysics::new(100.0, 1.0, 3.0, 3.0, -9.81, 100.0);
+        let mut controller = build_controller(&encoder, &motor);
+        // ka is the car's mass over the motor constant
+        controller.set_feedforward(FeedForward::new(9.81, 0.0, 1.0));
+        controller.set_motion_profile(Some(MotionConstraints {
+            max_velocity: 1.0,
+            max_acceleration: 0.5,
+        }));
+        controller.set_target_floor(2);
+
+        let dt = 1.0 / 60.0;
+        let mut highest: f32 = 0.0;
+        for _ in 0..(60 * 20) {
+            encoder
+                .borrow_mut()
+                .set_position(physics.get_position() as f64);
+            controller.tick(dt);
+            assert!(
+                controller.get_setpoint_velocity().abs() <= 1.0,
+                "commanded velocity {} over the limit",
+                controller.get_setpoint_velocity()
+            );
+            physics.set_voltage(motor.borrow().get_voltage() as f32);
+            physics.update();
+            highest = highest.max(physics.get_position());
+        }
+
+        assert_eq!(controller.get_setpoint_height(), 10.0);
+        assert!(
+            controller.has_reached_target(),
+            "car should settle at floor 2, at {}",
+            physics.get_position()
+        );
+        assert!(
+            highest < 10.1,
+            "profiled move should not overshoot, peaked at {}",
+            highest
+        );
+    }
+
+    #[test]
+    fn test_profile_restarts_after_brake_or_fault() {
+        let encoder = Rc::new(RefCell::new(SimulatedEncoder::new(0.0)));
+        let motor = Rc::new(RefCell::new(SimulatedMotor::new()));
+        let mut controller = build_controller(&encoder, &motor);
+        controller.set_motion_profile(Some(MotionConstraints {
+            max_velocity: 1.0,
+            max_acceleration: 0.5,
+        }));
+        controller.set_target_floor(2);
+
+        // the brake holds the car at 0m while the setpoint runs on to the target
+        let dt = 1.0 / 60.0;
+        controller.set_brake_engaged(true);
+        for _ in 0..(60 * 20) {
+            controller.tick(dt);
+        }
+        assert_eq!(controller.get_setpoint_height(), 10.0);
+
+        controller.set_brake_engaged(false);
+        controller.tick(dt);
+        assert!(
+            controller.get_setpoint_height() < 0.01,
+            "released car should start a new profile from 0m, setpoint at {}",
+            controller.get_setpoint_height()
+        );
+
+        for _ in 0..(60 * 20) {
+            controller.tick(dt);
+        }
+        assert_eq!(controller.get_setpoint_height(), 10.0);
+        controller.clear_fault();
+        controller.tick(dt);
+        assert!(
+            controller.get_setpoint_height() < 0.01,
+            "cleared fault should start a new profile from 0m, setpoint at {}",
+            controller.get_setpoint_height()
+        );
+    }
+
+    fn peak_start_acceleration(soft_start: Option<f64>) -> f32 {
+        let encoder = Rc::new(RefCell::new(SimulatedEncoder::new(0.0)));
+        let motor = Rc::new(RefCell::new(SimulatedMotor::new()));
+        let mut physics = ElevatorPhysics::new(100.0, 1.0, 3.0, 3.10, -9.81, 100.0);
+        let mut controller = build_controller(&encoder, &motor);
+        controller.set_soft_start(soft_start);
+        controller.set_target_floor(1);
+
+        let dt = 1.0 / 60.0;
+        let mut peak: f32 = 0.0;
+        for _ in 0..30 {
+            encoder
+                .borrow_mut()
+                .set_position(physics.get_position() as f64);
+            controller.tick(dt);
+            physics.set_voltage(motor.borrow().get_voltage() as f32);
+            physics.update();
+            peak = peak.max(physics.get_acceleration().abs());
+        }
+        peak
+    }
+
+    #[test]
+    fn test_soft_start_limits_initial_acceleration() {
+        let hard = peak_start_acceleration(None);
+        let soft = peak_start_acceleration(Some(1.0));
+
+        assert!(
+            soft < hard * 0.75,
+            "soft start peak {} should be well below {}",
+            soft,
+            hard
+        );
+    }
+
+    #[test]
+    fn test_doors_open_before_leveling_completes() {
+        let encoder = Rc::new(RefCell::new(SimulatedEncoder::new(0.0)));
+        let motor = Rc::new(RefCell::new(SimulatedMotor::new()));
+        let mut controller = build_controller(&encoder, &motor);
+        controller.set_door_open_precision(0.25);
+        controller.set_releveling_precision(0.02);
+        controller.set_target_floor(1);
+
+        // 0.2m / 0.04 floors short of floor 1
+        encoder.borrow_mut().set_position(4.8);
+        assert!(controller.can_open_doors(), "inside the door open band");
+        assert!(
+            !controller.has_reached_target(),
+            "departure waits for leveling"
+        );
+
+        encoder.borrow_mut().set_position(4.99);
+        assert!(controller.can_open_doors());
+        assert!(controller.has_reached_target());
+
+        encoder.borrow_mut().set_position(4.0);
+        assert!(!controller.can_open_doors());
     }
 }
@@ -0,0 +1,194 @@
+use crate::core::{
+    Elevator, ElevatorDirection, ElevatorRequest, ElevatorRequestErr, ElevatorState, ElevatorUnit,
+};
+
+// added to a car's cost when it has to turn around before it can serve a call
+const REVERSAL_PENALTY: u32 = 100;
+
+// a bank of cars sharing the hall buttons. hall calls are assigned to the cheapest car,
+// car calls stay with the car they were made in. Box<dyn ElevatorUnit> mixes car types
+pub struct GroupController<U: ElevatorUnit = Elevator> {
+    cars: Vec<U>,
+}
+
+impl<U: ElevatorUnit> GroupController<U> {
+    pub fn new(cars: Vec<U>) -> Self {
+        Self { cars }
+    }
+
+    // assigns the call to the best car that accepts it, returns that car's index.
+    // a call a car already has queued stays with that car
+    pub fn dispatch(&mut self, request: ElevatorRequest) -> Result<usize, ElevatorRequestErr> {
+        if let Some(index) = self.cars.iter().position(|car| car.has_request(request)) {
+            // lights the hall button if a car call queued the stop
+            self.cars[index].hall_call(request)?;
+            return Ok(index);
+        }
+
+        let mut candidates: Vec<_> = self
+            .cars
+            .iter()
+            .enumerate()
+            .filter_map(|(index, car)| {
+                get_cost(car, request).map(|cost| (cost, car.get_request_count(), index))
+            })
+            .collect();
+        candidates.sort();
+
+        let mut error = ElevatorRequestErr::DENIED;
+        for (_, _, index) in candidates {
+            match self.cars[index].hall_call(request) {
+                Ok(_) => return Ok(index),
+                Err(e) => error = e,
+            }
+        }
+
+        Err(error)
+    }
+
+    pub fn car_call(&mut self, car: usize, floor: i8) -> Result<bool, ElevatorRequestErr> {
+        match self.cars.get_mut(car) {
+            Some(elevator) => elevator.car_call(floor),
+            None => Err(ElevatorRequestErr::DENIED),
+        }
+    }
+
+    pub fn state_loop(&mut self, dt: f64) {
+        for car in &mut self.cars {
+            car.state_loop(dt);
+        }
+    }
+
+    pub fn get_cars(&self) -> &[U] {
+        &self.cars
+    }
+
+    pub fn get_car(&self, car: usize) -> Option<&U> {
+        self.cars.get(car)
+    }
+
+    pub fn get_mut_car(&mut self, car: usize) -> Option<&mut U> {
+        self.cars.get_mut(car)
+    }
+}
+
+// floors to travel before serving the call. a car that already left the call behind,
+// or is heading the other way, first runs out its trip and turns around
+fn get_cost(car: &impl ElevatorUnit, request: ElevatorRequest) -> Option<u32> {
+    let current = car.get_current_floor();
+    let distance = current.abs_diff(request.floor) as u32;
+
+    let direction = match car.get_state() {
+        ElevatorState::IDLE => return Some(distance),
+        ElevatorState::INSPECTION => return None,
+        ElevatorState::MOVING(direction) | ElevatorState::WAITING(direction, _) => *direction,
+    };
+
+    let ahead = match direction {
+        ElevatorDirection::UP => request.floor > current,
+        ElevatorDirection::DOWN => request.floor < current,
+    };
+    // a stopped car can still take a call at its own floor
+    let at_car = request.floor == current && !matches!(car.get_state(), ElevatorState::MOVING(_));
+
+    if request.direction == direction && (ahead || at_car) {
+        return Some(distance);
+    }
+
+    let target = car.get_target_floor();
+    let turnaround = current.abs_diff(target) as u32 + target.abs_diff(request.floor) as u32;
+    Some(turnaround + REVERSAL_PENALTY)
+}
+
+#[cfg(test)]
+mod group_tests {
+    use super::*;
+
+    fn idle_car_at(floor: i8) -> Elevator {
+        let mut car = Elevator::new();
+        car.set_current_floor(floor);
+        car
+    }
+
+    #[test]
+    fn test_nearest_idle_car_is_sent() {
+        let mut group = GroupController::new(vec![idle_car_at(1), idle_car_at(10)]);
+
+        assert_eq!(
+            group.dispatch(ElevatorRequest::new(ElevatorDirection::UP, 3)),
+            Ok(0)
+        );
+        assert_eq!(group.get_cars()[0].get_all_requests().count(), 1);
+        assert_eq!(group.get_cars()[1].get_all_requests().count(), 0);
+
+        group.state_loop(0.1);
+        assert_eq!(
+            *group.get_cars()[0].get_state(),
+            ElevatorState::MOVING(ElevatorDirection::UP)
+        );
+        assert_eq!(*group.get_cars()[1].get_state(), ElevatorState::IDLE);
+    }
+
+    #[test]
+    fn test_car_committed_past_the_call_is_skipped() {
+        let mut group = GroupController::new(vec![idle_car_at(6), idle_car_at(0)]);
+        assert_eq!(group.car_call(0, 9), Ok(true));
+        group.state_loop(0.1);
+        assert_eq!(
+            *group.get_cars()[0].get_state(),
+            ElevatorState::MOVING(ElevatorDirection::UP)
+        );
+
+        // one floor from the rising car, five from the idle one
+        assert_eq!(
+            group.dispatch(ElevatorRequest::new(ElevatorDirection::DOWN, 5)),
+            Ok(1)
+        );
+        // on the rising car's way and in its direction
+        assert_eq!(
+            group.dispatch(ElevatorRequest::new(ElevatorDirection::UP, 8)),
+            Ok(0)
+        );
+    }
+
+    #[test]
+    fn test_ties_go_to_the_less_busy_car() {
+        let mut group = GroupController::new(vec![idle_car_at(2), idle_car_at(2)]);
+        assert_eq!(
+            group.dispatch(ElevatorRequest::new(ElevatorDirection::UP, 4)),
+            Ok(0)
+        );
+        assert_eq!(
+            group.dispatch(ElevatorRequest::new(ElevatorDirection::DOWN, 4)),
+            Ok(1),
+            "both cars are idle at floor 2, car 0 already has a call"
+        );
+        assert_eq!(group.car_call(5, 1), Err(ElevatorRequestErr::DENIED));
+    }
+
+    #[test]
+    fn test_repeated_dispatch_keeps_the_assigned_car() {
+        let mut group = GroupController::new(vec![idle_car_at(2), idle_car_at(2)]);
+        let request = ElevatorRequest::new(ElevatorDirection::UP, 4);
+
+        assert_eq!(group.dispatch(request), Ok(0));
+        assert_eq!(
+            group.dispatch(request),
+            Ok(0),
+            "the call is already with car 0, no second car is sent"
+        );
+        assert_eq!(group.get_cars()[0].get_all_requests().count(), 1);
+        assert_eq!(group.get_cars()[1].get_all_requests().count(), 0);
+
+        // a stop queued by a car call takes the hall call too
+        assert_eq!(group.car_call(1, 7), Ok(true));
+        assert_eq!(
+            group.dispatch(ElevatorRequest::new(ElevatorDirection::UP, 7)),
+            Ok(1)
+        );
+        assert_eq!(
+            group.get_cars()[1].lit_buttons(),
+            vec![(7, ElevatorDirection::UP)]
+        );
+    }
+}
@@ -1,5 +1,11 @@
 mod elevator_control;
+mod group;
+mod motion_profile;
 mod pid;
+mod velocity_control;
 
 pub use elevator_control::*;
+pub use group::*;
+pub use motion_profile::*;
 pub use pid::*;
+pub use velocity_control::*;
@@ -0,0 +1,173 @@
+// limits for the commanded motion, both positive
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct MotionConstraints {
+    pub max_velocity: f64,     // m/s
+    pub max_acceleration: f64, // m/s^2
+}
+
+// trapezoidal setpoint generator. it is planned one step at a time from the current
+// setpoint, so a goal that changes mid-flight is picked up without a jump in velocity
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct MotionProfile {
+    position: f64,
+    velocity: f64,
+    acceleration: f64,
+}
+
+impl MotionProfile {
+    pub fn new(position: f64) -> Self {
+        Self {
+            position,
+            velocity: 0.0,
+            acceleration: 0.0,
+        }
+    }
+
+    pub fn step(&mut self, goal: f64, constraints: MotionConstraints, dt: f64) {
+        let distance = goal - self.position;
+        let direction = distance.signum();
+        let max_delta = constraints.max_acceleration * dt;
+
+        // fastest speed this step that still stops at the goal: the distance left after
+        // the step must cover the v^2/(2a) of braking from it
+        let half_step = max_delta / 2.0;
+        let remaining = (distance.abs() - self.velocity * direction * dt / 2.0).max(0.0);
+        let stopping_velocity =
+            (half_step * half_step + 2.0 * constraints.max_acceleration * remaining).sqrt()
+                - half_step;
+        let target_velocity = direction * stopping_velocity.min(constraints.max_velocity);
+
+        // braking included, the velocity never changes by more than max_acceleration.
+        // a goal moved inside the stopping distance is overshot and approached again
+        let velocity =
+            self.velocity + (target_velocity - self.velocity).clamp(-max_delta, max_delta);
+        let position = self.position + (self.velocity + velocity) / 2.0 * dt;
+
+        // arrived, or the last step would pass the goal slow enough to stop there
+        let crossed = (goal - position).signum() != direction;
+        if (distance == 0.0 || crossed) && self.velocity.abs() <= max_delta {
+            self.acceleration = -self.velocity / dt;
+            self.position = goal;
+            self.velocity = 0.0;
+            return;
+        }
+
+        self.acceleration = (velocity - self.velocity) / dt;
+        self.position = position;
+        self.velocity = velocity;
+    }
+
+    pub fn is_at(&self, goal: f64) -> bool {
+        self.position == goal && self.velocity == 0.0
+    }
+
+    pub fn get_position(&self) -> f64 {
+        self.position
+    }
+
+    pub fn get_velocity(&self) -> f64 {
+        self.velocity
+    }
+
+    pub fn get_acceleration(&self) -> f64 {
+        self.acceleration
+    }
+}
+
+#[cfg(test)]
+mod motion_profile_tests {
+    use super::*;
+
+    const CONSTRAINTS: MotionConstraints = MotionConstraints {
+        max_velocity: 1.5,
+        max_acceleration: 0.8,
+    };
+    const DT: f64 = 1.0 / 60.0;
+
+    #[test]
+    fn test_profile_respects_constraints() {
+        let mut profile = MotionProfile::new(0.0);
+        let mut steps = 0;
+
+        while !profile.is_at(15.0) {
+            profile.step(15.0, CONSTRAINTS, DT);
+            steps += 1;
+            assert!(
+                profile.get_velocity() <= CONSTRAINTS.max_velocity,
+                "velocity {} over the limit",
+                profile.get_velocity()
+            );
+            assert!(profile.get_position() <= 15.0, "setpoint overshoots");
+            assert!(steps < 60 * 60, "profile never reaches the goal");
+        }
+
+        // 15m: ~1.9s ramp up, ~8.1s cruise, ~1.9s ramp down
+        let duration = steps as f64 * DT;
+        assert!(
+            (duration - 11.9).abs() < 0.2,
+            "trapezoid should take about 11.9s, took {}",
+            duration
+        );
+    }
+
+    #[test]
+    fn test_goal_change_keeps_velocity_continuous() {
+        let mut profile = MotionProfile::new(0.0);
+        for _ in 0..180 {
+            profile.step(15.0, CONSTRAINTS, DT);
+        }
+        let cruising = profile.get_velocity();
+        assert!(cruising > 1.0);
+
+        // an en-route stop shortens the trip while cruising
+        let mut previous = profile.get_velocity();
+        let mut steps = 0;
+        while !profile.is_at(5.0) {
+            profile.step(5.0, CONSTRAINTS, DT);
+            steps += 1;
+            assert!(
+                (profile.get_velocity() - previous).abs()
+                    <= CONSTRAINTS.max_acceleration * DT + 1e-9,
+                "velocity jumped from {} to {}",
+                previous,
+                profile.get_velocity()
+            );
+            previous = profile.get_velocity();
+            assert!(steps < 60 * 60, "profile never reaches the new goal");
+        }
+        assert_eq!(profile.get_position(), 5.0);
+    }
+
+    #[test]
+    fn test_acceleration_stays_within_the_limit() {
+        let limit = CONSTRAINTS.max_acceleration + 1e-9;
+        let mut profile = MotionProfile::new(0.0);
+        let mut previous = 0.0;
+        let mut step_checked = |profile: &mut MotionProfile, goal: f64| {
+            profile.step(goal, CONSTRAINTS, DT);
+            assert!(
+                ((profile.get_velocity() - previous) / DT).abs() <= limit,
+                "velocity changed from {} to {} in one step",
+                previous,
+                profile.get_velocity()
+            );
+            assert!(profile.get_acceleration().abs() <= limit);
+            previous = profile.get_velocity();
+        };
+
+        // cruising each way, then a goal moved inside the stopping distance
+        for goal in [15.0, 0.0] {
+            for _ in 0..360 {
+                step_checked(&mut profile, goal);
+            }
+            let near_goal = profile.get_position() + 0.5 * profile.get_velocity().signum();
+
+            let mut steps = 0;
+            while !profile.is_at(near_goal) {
+                step_checked(&mut profile, near_goal);
+                steps += 1;
+                assert!(steps < 60 * 60, "profile never reaches the goal");
+            }
+        }
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::VecDeque;
+
 pub struct PIDController {
     kp: f64,
     ki: f64,
@@ -6,6 +8,9 @@ pub struct PIDController {
     previous_error: f64,
     min_limit: f64,
     max_limit: f64,
+    derivative_window: usize,
+    samples: VecDeque<(f64, f64)>, // (time, error)
+    time: f64,
 }
 
 impl PIDController {
@@ -18,12 +23,55 @@ impl PIDController {
             previous_error: 0.0,
             min_limit: -f64::INFINITY,
             max_limit: f64::INFINITY,
+            derivative_window: 1,
+            samples: VecDeque::new(),
+            time: 0.0,
         }
     }
 
     pub fn reset(&mut self) {
         self.integral = 0.0;
         self.previous_error = 0.0;
+        self.samples.clear();
+        self.time = 0.0;
+    }
+
+    // derivative as the least-squares slope over the last `window` steps, 1 is a plain difference
+    pub fn set_derivative_window(&mut self, window: usize) {
+        self.derivative_window = window.max(1);
+        self.samples.clear();
+    }
+
+    fn get_derivative(&mut self, error: f64, dt: f64) -> f64 {
+        let single_step = (error - self.previous_error) / dt;
+        if self.derivative_window == 1 {
+            return single_step;
+        }
+
+        self.time += dt;
+        self.samples.push_back((self.time, error));
+        while self.samples.len() > self.derivative_window + 1 {
+            self.samples.pop_front();
+        }
+
+        if self.samples.len() < 2 {
+            return single_step;
+        }
+
+        let n = self.samples.len() as f64;
+        let mean_time = self.samples.iter().map(|(t, _)| t).sum::<f64>() / n;
+        let mean_error = self.samples.iter().map(|(_, e)| e).sum::<f64>() / n;
+        let (covariance, variance) =
+            self.samples
+                .iter()
+                .fold((0.0, 0.0), |(covariance, variance), (t, e)| {
+                    (
+                        covariance + (t - mean_time) * (e - mean_error),
+                        variance + (t - mean_time).powi(2),
+                    )
+                });
+
+        covariance / variance
     }
 
     pub fn get_integral(&self) -> f64 {
@@ -32,7 +80,7 @@ impl PIDController {
 
     pub fn update(&mut self, error: f64, dt: f64) -> f64 {
         self.integral += error * dt;
-        let derivative = (error - self.previous_error) / dt;
+        let derivative = self.get_derivative(error, dt);
         self.previous_error = error;
 
         let raw_voltage = self.kp * error + self.ki * self.integral + self.kd * derivative;
@@ -57,3 +105,56 @@ impl FeedForward {
         Self { kg, kv, ka }
     }
 }
+
+#[cfg(test)]
+mod pid_tests {
+    use super::*;
+
+    fn worst_slope_error(window: usize) -> f64 {
+        let mut pid = PIDController::new(0.0, 0.0, 1.0);
+        pid.set_derivative_window(window);
+
+        let dt = 0.01;
+        let mut worst: f64 = 0.0;
+        for step in 0..200 {
+            let time = step as f64 * dt;
+            let noise = if step % 2 == 0 { 0.05 } else { -0.05 };
+            let derivative = pid.update(2.0 * time + noise, dt);
+
+            // skip the start-up kick
+            if step >= 50 {
+                worst = worst.max((derivative - 2.0).abs());
+            }
+        }
+        worst
+    }
+
+    #[test]
+    fn test_windowed_derivative_on_noisy_ramp() {
+        let single_step = worst_slope_error(1);
+        let windowed = worst_slope_error(20);
+
+        assert!(
+            windowed < 0.5,
+            "windowed slope should be close to 2.0, off by {}",
+            windowed
+        );
+        assert!(
+            windowed < single_step / 10.0,
+            "windowed {} should beat the single step {}",
+            windowed,
+            single_step
+        );
+    }
+
+    #[test]
+    fn test_window_of_one_is_single_step() {
+        let mut plain = PIDController::new(1.0, 0.5, 2.0);
+        let mut windowed = PIDController::new(1.0, 0.5, 2.0);
+        windowed.set_derivative_window(1);
+
+        for error in [1.0, 0.8, 0.5, 0.55, 0.2] {
+            assert_eq!(plain.update(error, 0.1), windowed.update(error, 0.1));
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::control::elevator_control::{ElevatorController, estimate_floor};
+use crate::control::pid::{FeedForward, PIDController};
+use crate::core::{Encoder, Motor};
+
+pub struct ElevatorVelocityController<'a> {
+    encoder: Rc<RefCell<dyn Encoder + 'a>>,
+    motor: Rc<RefCell<dyn Motor + 'a>>,
+    pid: PIDController,
+    ff: FeedForward,
+    voltage_limit: f64,
+    floor_height: f64,
+    target_floor: i8,
+    precision: f64,
+    velocity_setpoint: f64,
+}
+
+impl<'a> ElevatorVelocityController<'a> {
+    pub fn new(
+        encoder: Rc<RefCell<impl Encoder + 'a>>,
+        motor: Rc<RefCell<impl Motor + 'a>>,
+        voltage_limit: f64,
+        mut pid: PIDController,
+        ff: FeedForward,
+        floor_height: f64,
+        precision: f64,
+    ) -> Self {
+        pid.set_output_limits(-voltage_limit - ff.kg, voltage_limit - ff.kg);
+
+        ElevatorVelocityController {
+            encoder,
+            motor,
+            pid,
+            ff,
+            voltage_limit,
+            floor_height,
+            precision,
+            target_floor: 0,
+            velocity_setpoint: 0.0,
+        }
+    }
+
+    pub fn set_velocity_setpoint(&mut self, velocity: f64) {
+        self.velocity_setpoint = velocity;
+    }
+
+    pub fn get_velocity_setpoint(&self) -> f64 {
+        self.velocity_setpoint
+    }
+
+    pub fn get_current_velocity(&self) -> f64 {
+        self.encoder.borrow().get_velocity()
+    }
+}
+
+impl ElevatorController for ElevatorVelocityController<'_> {
+    fn set_target_floor(&mut self, floor: i8) {
+        self.target_floor = floor;
+    }
+
+    fn tick(&mut self, dt: f64) {
+        let error = self.velocity_setpoint - self.encoder.borrow().get_velocity();
+
+        let voltage = self.pid.update(error, dt) + self.ff.kv * self.velocity_setpoint + self.ff.kg;
+        self.motor
+            .borrow_mut()
+            .set_voltage(voltage.clamp(-self.voltage_limit, self.voltage_limit));
+    }
+
+    fn get_current_floor(&self) -> Option<i8> {
+        estimate_floor(
+            self.encoder.borrow().get_position(),
+            self.floor_height,
+            self.precision,
+        )
+    }
+
+    fn has_reached_target(&self) -> bool {
+        let current = self.encoder.borrow().get_position();
+        let target = self.target_floor as f64 * self.floor_height;
+
+        (current - target).abs() < self.precision
+    }
+}
+
+#[cfg(test)]
+mod velocity_control_tests {
+    use super::*;
+    use crate::{ElevatorPhysics, SimulatedEncoder, SimulatedMotor};
+
+    #[test]
+    fn test_velocity_converges_to_setpoint() {
+        let encoder = Rc::new(RefCell::new(SimulatedEncoder::new(0.0)));
+        let motor = Rc::new(RefCell::new(SimulatedMotor::new()));
+        let mut physics = ElevatorPhysics::new(100.0, 1.0, 3.0, 3.10, -9.81, 100.0);
+
+        let mut controller = ElevatorVelocityController::new(
+            Rc::clone(&encoder),
+            Rc::clone(&motor),
+            12.0,
+            PIDController::new(20.0, 5.0, 0.0),
+            FeedForward::new(9.81, 0.0, 0.0),
+            5.0,
+            0.1,
+        );
+        controller.set_velocity_setpoint(1.0);
+
+        let dt = 1.0 / 60.0;
+        for _ in 0..300 {
+            controller.tick(dt);
+            physics.set_voltage(motor.borrow().get_voltage() as f32);
+            physics.update();
+
+            let mut encoder = encoder.borrow_mut();
+            encoder.set_position(physics.get_position() as f64);
+            encoder.set_velocity(physics.get_velocity() as f64);
+        }
+
+        assert!(
+            (controller.get_current_velocity() - 1.0).abs() < 0.05,
+            "velocity should settle at the setpoint, got {}",
+            controller.get_current_velocity()
+        );
+    }
+}
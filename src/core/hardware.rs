@@ -1,6 +1,12 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+
 pub trait Encoder {
     fn get_position(&self) -> f64;
     fn set_position(&mut self, position: f64);
+    fn get_velocity(&self) -> f64;
+    fn set_velocity(&mut self, velocity: f64);
 }
 
 pub trait Motor {
@@ -10,3 +16,61 @@ pub trait Motor {
 pub trait Button {
     fn press(&mut self);
 }
+
+pub trait LoadCell {
+    fn get_load(&self) -> f64;
+}
+
+pub trait FloorSensor {
+    fn get_floor(&self) -> Option<i8>;
+}
+
+// hardware shared on one thread, each call borrows it
+impl<T: Encoder + ?Sized> Encoder for Rc<RefCell<T>> {
+    fn get_position(&self) -> f64 {
+        self.borrow().get_position()
+    }
+
+    fn set_position(&mut self, position: f64) {
+        self.borrow_mut().set_position(position);
+    }
+
+    fn get_velocity(&self) -> f64 {
+        self.borrow().get_velocity()
+    }
+
+    fn set_velocity(&mut self, velocity: f64) {
+        self.borrow_mut().set_velocity(velocity);
+    }
+}
+
+impl<T: Motor + ?Sized> Motor for Rc<RefCell<T>> {
+    fn set_voltage(&mut self, voltage: f64) {
+        self.borrow_mut().set_voltage(voltage);
+    }
+}
+
+// hardware shared across threads, each call takes the lock
+impl<T: Encoder + ?Sized> Encoder for Arc<Mutex<T>> {
+    fn get_position(&self) -> f64 {
+        self.lock().unwrap().get_position()
+    }
+
+    fn set_position(&mut self, position: f64) {
+        self.lock().unwrap().set_position(position);
+    }
+
+    fn get_velocity(&self) -> f64 {
+        self.lock().unwrap().get_velocity()
+    }
+
+    fn set_velocity(&mut self, velocity: f64) {
+        self.lock().unwrap().set_velocity(velocity);
+    }
+}
+
+impl<T: Motor + ?Sized> Motor for Arc<Mutex<T>> {
+    fn set_voltage(&mut self, voltage: f64) {
+        self.lock().unwrap().set_voltage(voltage);
+    }
+}
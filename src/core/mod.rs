@@ -1,6 +1,12 @@
 mod hardware;
+mod sensors;
 mod state;
+mod timer;
+mod unit;
 
 // re-export
 pub use hardware::*;
+pub use sensors::*;
 pub use state::*;
+pub use timer::*;
+pub use unit::*;
@@ -0,0 +1,98 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::core::{Encoder, FloorSensor, LoadCell};
+
+// readings of every installed sensor at one tick
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct SensorSnapshot {
+    pub position: f64,
+    pub velocity: f64,
+    pub load: Option<f64>,
+    pub floor: Option<i8>,
+}
+
+pub struct Sensors<'a> {
+    encoder: Rc<RefCell<dyn Encoder + 'a>>,
+    load_cell: Option<Rc<RefCell<dyn LoadCell + 'a>>>,
+    floor_sensor: Option<Rc<RefCell<dyn FloorSensor + 'a>>>,
+}
+
+impl<'a> Sensors<'a> {
+    pub fn new(encoder: Rc<RefCell<impl Encoder + 'a>>) -> Self {
+        Sensors {
+            encoder,
+            load_cell: None,
+            floor_sensor: None,
+        }
+    }
+
+    pub fn with_load_cell(mut self, load_cell: Rc<RefCell<impl LoadCell + 'a>>) -> Self {
+        self.load_cell = Some(load_cell);
+        self
+    }
+
+    pub fn with_floor_sensor(mut self, floor_sensor: Rc<RefCell<impl FloorSensor + 'a>>) -> Self {
+        self.floor_sensor = Some(floor_sensor);
+        self
+    }
+
+    pub fn snapshot(&self) -> SensorSnapshot {
+        let encoder = self.encoder.borrow();
+
+        SensorSnapshot {
+            position: encoder.get_position(),
+            velocity: encoder.get_velocity(),
+            load: self
+                .load_cell
+                .as_ref()
+                .map(|load_cell| load_cell.borrow().get_load()),
+            floor: self
+                .floor_sensor
+                .as_ref()
+                .and_then(|floor_sensor| floor_sensor.borrow().get_floor()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod sensors_tests {
+    use super::*;
+    use crate::{SimulatedEncoder, SimulatedFloorSensor, SimulatedLoadCell};
+
+    #[test]
+    fn test_snapshot_reflects_each_sensor() {
+        let encoder = Rc::new(RefCell::new(SimulatedEncoder::new(2.5)));
+        let load_cell = Rc::new(RefCell::new(SimulatedLoadCell::new(0.0)));
+        let floor_sensor = Rc::new(RefCell::new(SimulatedFloorSensor::new()));
+
+        let encoder_only = Sensors::new(Rc::clone(&encoder));
+        let sensors = Sensors::new(Rc::clone(&encoder))
+            .with_load_cell(Rc::clone(&load_cell))
+            .with_floor_sensor(Rc::clone(&floor_sensor));
+
+        encoder.borrow_mut().set_velocity(-0.4);
+        load_cell.borrow_mut().set_load(240.0);
+        floor_sensor.borrow_mut().set_floor(Some(1));
+
+        assert_eq!(
+            sensors.snapshot(),
+            SensorSnapshot {
+                position: 2.5,
+                velocity: -0.4,
+                load: Some(240.0),
+                floor: Some(1),
+            }
+        );
+        assert_eq!(
+            encoder_only.snapshot(),
+            SensorSnapshot {
+                position: 2.5,
+                velocity: -0.4,
+                load: None,
+                floor: None,
+            },
+            "missing sensors are reported as None"
+        );
+    }
+}
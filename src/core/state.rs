@@ -1,14 +1,115 @@
-use core::panic;
 use std::{
-    cmp::Ordering,
-    collections::{HashSet, hash_set},
+    cell::RefCell,
+    cmp::{Ordering, Reverse},
+    collections::{HashMap, HashSet, hash_set},
+    ops::RangeInclusive,
+    rc::Rc,
 };
 
-#[derive(PartialEq, Eq, Hash, Debug)]
+use crate::core::DwellTimer;
+
+const DOOR_OPEN_TIME: f64 = 4.0; // s, doors held open at a stop
+const DOOR_CLOSE_TIME: f64 = 1.0; // s, doors closing before the car may leave
+const ACCESSIBLE_DWELL_TIME: f64 = 20.0;
+const LOAD_CHANGE_TOLERANCE: f64 = 1.0; // kg
+const PARKING_TIMEOUT: f64 = 30.0; // s idle before the car parks
+const PLAN_STEP: f64 = 1.0; // s, one floor of travel per step in simulate_plan
+const PLAN_MAX_STEPS: usize = 10_000;
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
 pub enum ElevatorRequestErr {
     DUPLICATE,
     DENIED,
     CurrentFloor,
+    OutOfRange,
+    QueueFull,
+    InvalidDirection,
+    Locked,
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum ElevatorConfigErr {
+    InvalidDoorTimes(f64, f64),
+    EmptyFloorRange(i8, i8),
+    StartOutOfRange(i8),
+}
+
+// fixed for the life of the car, see Elevator::with_config
+#[derive(PartialEq, Clone, Debug)]
+pub struct ElevatorConfig {
+    pub door_open_time: f64,  // s, doors held open at a stop
+    pub door_close_time: f64, // s, doors closing before the car may leave
+    // the floors the building has, calls outside them are denied
+    pub floor_range: RangeInclusive<i8>,
+    pub start_floor: i8,
+}
+
+impl Default for ElevatorConfig {
+    fn default() -> Self {
+        Self {
+            door_open_time: DOOR_OPEN_TIME,
+            door_close_time: DOOR_CLOSE_TIME,
+            floor_range: i8::MIN..=i8::MAX,
+            start_floor: 0,
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum DeferredReason {
+    Inspection,
+}
+
+// result of an accepted call, `deferred_reason` is set when it cannot be served yet
+// and `cancelled` when a lit car call was pressed again
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub struct CallOutcome {
+    pub accepted: bool,
+    pub deferred_reason: Option<DeferredReason>,
+    pub cancelled: bool,
+}
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, Default)]
+pub enum IdlePriority {
+    #[default]
+    PreferUp,
+    PreferDown,
+    PreferNearest,
+}
+
+// where an idle car goes after the parking timeout
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, Default)]
+pub enum ParkingPolicy {
+    // stays where it last stopped
+    #[default]
+    LastStop,
+    // floor 0, or the served floor nearest to it
+    Lobby,
+    // middle of the service zone
+    Middle,
+}
+
+// dwell that closes once the load has been steady for `settle_time`, within `min..=max`
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct AdaptiveDwell {
+    pub min: f64,
+    pub max: f64,
+    pub settle_time: f64,
+}
+
+// why the dispatcher picked the current target
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum DispatchReason {
+    IdleFirstUp,
+    IdleFirstDown,
+    IdleNearest,
+    EnRouteSameDirection,
+    OppositeDirectionExtreme,
+    OppositeDirectionFirst,
+    SameDirectionFirst,
+    PriorityCall,
+    CloserReversal,
+    Parking,
 }
 
 #[derive(PartialEq, Eq, Hash, Debug)]
@@ -16,6 +117,14 @@ pub enum ElevatorFloorReachErr {
     NotMoving,
 }
 
+#[cfg(feature = "debug-invariants")]
+#[derive(PartialEq, Debug)]
+pub enum InvariantViolation {
+    TargetOutsideServiceZone(i8),
+    MovingWithoutRequest(i8),
+    DwellOutOfRange(f64),
+}
+
 #[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
 pub enum ElevatorDirection {
     UP,
@@ -42,6 +151,7 @@ pub enum ElevatorState {
     MOVING(ElevatorDirection),
     WAITING(ElevatorDirection, ElevatorDoorsState),
     IDLE,
+    INSPECTION,
 }
 
 #[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
@@ -64,13 +174,89 @@ impl ElevatorRequest {
     }
 }
 
-#[derive(PartialEq, Debug)]
+// boxed closure held by the state, closures cannot be printed or compared so any two
+// are equal and the holder is compared by its other fields. a clone shares the closure
+pub struct Callback<F: ?Sized>(Rc<RefCell<Box<F>>>);
+
+impl<F: ?Sized> Clone for Callback<F> {
+    fn clone(&self) -> Self {
+        Callback(Rc::clone(&self.0))
+    }
+}
+
+impl<F: ?Sized> std::fmt::Debug for Callback<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Callback")
+    }
+}
+
+impl<F: ?Sized> PartialEq for Callback<F> {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+// scripted hold, the car stays WAITING at `floor` until `condition` returns true
+#[derive(PartialEq, Clone, Debug)]
+pub struct FloorHold {
+    floor: i8,
+    condition: Callback<dyn FnMut() -> bool>,
+}
+
+// warns once when the trip count reaches `threshold`
+#[derive(PartialEq, Clone, Debug)]
+pub struct MaintenanceAlert {
+    threshold: u64,
+    on_due: Callback<dyn FnMut()>,
+}
+
+#[derive(PartialEq, Clone, Debug)]
 pub struct Elevator {
     current_floor: i8,
     target_floor: i8,
     state: ElevatorState,
     request_buffer: HashSet<ElevatorRequest>,
-    waiting_time: f64,
+    floor_range: RangeInclusive<i8>,
+    lit_hall_calls: HashSet<ElevatorRequest>,
+    lit_car_calls: HashSet<i8>,
+    request_capacity: HashMap<ElevatorDirection, usize>,
+    priority_request: Option<ElevatorRequest>,
+    waiting_passengers: HashMap<ElevatorRequest, u32>,
+    locked_floors: HashSet<i8>,
+    floor_hold: Option<FloorHold>,
+    reopen_on_same_floor_call: bool,
+    dwell_timer: DwellTimer,
+    door_open_time: f64,
+    door_close_time: f64,
+    service_zone: RangeInclusive<i8>,
+    accessible_dwell_time: f64,
+    floor_dwell_times: HashMap<i8, f64>,
+    adaptive_dwell: Option<AdaptiveDwell>,
+    load: f64,
+    load_settled_time: f64,
+    extended_dwell: bool,
+    last_decision: Option<DispatchReason>,
+    idle_priority: IdlePriority,
+    parking_policy: ParkingPolicy,
+    parking_timeout: f64,
+    parking_request: Option<ElevatorRequest>,
+    idle_timer: DwellTimer,
+    car_call_batch_window: Option<f64>,
+    car_call_batch: DwellTimer,
+    direction_hysteresis: bool,
+    origin_majority: bool,
+    at_origin: bool,
+    direction_inertia: Option<u8>,
+    committed_floor: i8,
+    coalescing_radius: Option<u8>,
+    seconds_per_floor: Option<f64>,
+    travel_timer: DwellTimer,
+    trip_count: u64,
+    door_cycle_count: u64,
+    maintenance_alert: Option<MaintenanceAlert>,
+    log_transitions: bool,
+    #[cfg(feature = "debug-invariants")]
+    panic_on_invariant_violation: bool,
 }
 
 impl Default for Elevator {
@@ -86,19 +272,251 @@ impl Elevator {
             target_floor: 0,
             state: ElevatorState::IDLE,
             request_buffer: HashSet::new(),
-            waiting_time: 0.0,
+            floor_range: i8::MIN..=i8::MAX,
+            lit_hall_calls: HashSet::new(),
+            lit_car_calls: HashSet::new(),
+            request_capacity: HashMap::new(),
+            priority_request: None,
+            waiting_passengers: HashMap::new(),
+            locked_floors: HashSet::new(),
+            floor_hold: None,
+            reopen_on_same_floor_call: true,
+            dwell_timer: DwellTimer::Inactive,
+            door_open_time: DOOR_OPEN_TIME,
+            door_close_time: DOOR_CLOSE_TIME,
+            service_zone: i8::MIN..=i8::MAX,
+            accessible_dwell_time: ACCESSIBLE_DWELL_TIME,
+            floor_dwell_times: HashMap::new(),
+            adaptive_dwell: None,
+            load: 0.0,
+            load_settled_time: 0.0,
+            extended_dwell: false,
+            last_decision: None,
+            idle_priority: IdlePriority::default(),
+            parking_policy: ParkingPolicy::default(),
+            parking_timeout: PARKING_TIMEOUT,
+            parking_request: None,
+            idle_timer: DwellTimer::Inactive,
+            car_call_batch_window: None,
+            car_call_batch: DwellTimer::Inactive,
+            direction_hysteresis: false,
+            origin_majority: false,
+            at_origin: false,
+            direction_inertia: None,
+            committed_floor: 0,
+            coalescing_radius: None,
+            seconds_per_floor: None,
+            travel_timer: DwellTimer::Inactive,
+            trip_count: 0,
+            door_cycle_count: 0,
+            maintenance_alert: None,
+            log_transitions: false,
+            #[cfg(feature = "debug-invariants")]
+            panic_on_invariant_violation: false,
+        }
+    }
+
+    pub fn with_config(config: ElevatorConfig) -> Result<Self, ElevatorConfigErr> {
+        let is_duration = |time: f64| time.is_finite() && time >= 0.0;
+        if !is_duration(config.door_open_time) || !is_duration(config.door_close_time) {
+            return Err(ElevatorConfigErr::InvalidDoorTimes(
+                config.door_open_time,
+                config.door_close_time,
+            ));
+        }
+        if config.floor_range.is_empty() {
+            return Err(ElevatorConfigErr::EmptyFloorRange(
+                *config.floor_range.start(),
+                *config.floor_range.end(),
+            ));
+        }
+        if !config.floor_range.contains(&config.start_floor) {
+            return Err(ElevatorConfigErr::StartOutOfRange(config.start_floor));
+        }
+
+        Ok(Self {
+            current_floor: config.start_floor,
+            target_floor: config.start_floor,
+            committed_floor: config.start_floor,
+            floor_range: config.floor_range,
+            door_open_time: config.door_open_time,
+            door_close_time: config.door_close_time,
+            ..Elevator::new()
+        })
+    }
+
+    // places the machine into an exact state, e.g. to unit test a single transition.
+    // a positive waiting time is the elapsed dwell of a car already serving its stop
+    pub fn from_parts(
+        current_floor: i8,
+        target_floor: i8,
+        state: ElevatorState,
+        requests: impl IntoIterator<Item = ElevatorRequest>,
+        waiting_time: f64,
+    ) -> Self {
+        let mut elevator = Elevator::new();
+        elevator.current_floor = current_floor;
+        elevator.target_floor = target_floor;
+        elevator.committed_floor = current_floor;
+        elevator.state = state;
+        elevator.request_buffer.extend(requests);
+
+        if matches!(elevator.state, ElevatorState::WAITING(_, _)) && waiting_time > 0.0 {
+            elevator.dwell_timer.start(elevator.get_dwell_time());
+            elevator.dwell_timer.tick(waiting_time);
+        }
+
+        elevator
+    }
+
+    // the dispatch state without callbacks, scripted holds are treated as released
+    fn copy_for_plan(&self) -> Elevator {
+        Elevator {
+            floor_hold: None,
+            maintenance_alert: None,
+            log_transitions: false,
+            ..self.clone()
+        }
+    }
+
+    // dry run of the dispatch logic on a copy, the stops in order until the car is idle
+    pub fn simulate_plan(&self) -> Vec<(i8, ElevatorDirection)> {
+        let mut copy = self.copy_for_plan();
+        let mut stops = Vec::new();
+        let mut was_waiting = matches!(copy.state, ElevatorState::WAITING(_, _));
+
+        for _ in 0..PLAN_MAX_STEPS {
+            if copy.state == ElevatorState::IDLE && copy.request_buffer.is_empty() {
+                break;
+            }
+
+            copy.advance_one_floor();
+            copy.update_state(PLAN_STEP);
+
+            let waiting = match copy.state {
+                ElevatorState::WAITING(direction, _) => Some(direction),
+                _ => None,
+            };
+            if let Some(direction) = waiting
+                && !was_waiting
+            {
+                stops.push((copy.current_floor, direction));
+            }
+            was_waiting = waiting.is_some();
         }
+
+        stops
     }
 
     pub fn hall_call(&mut self, request: ElevatorRequest) -> Result<bool, ElevatorRequestErr> {
+        self.hall_call_with_outcome(request)
+            .map(|outcome| outcome.accepted)
+    }
+
+    pub fn car_call(&mut self, floor: i8) -> Result<bool, ElevatorRequestErr> {
+        self.car_call_with_outcome(floor)
+            .map(|outcome| outcome.accepted)
+    }
+
+    pub fn hall_call_with_outcome(
+        &mut self,
+        request: ElevatorRequest,
+    ) -> Result<CallOutcome, ElevatorRequestErr> {
+        self.check_hall_call(request)?;
+
+        if let ElevatorState::WAITING(direction, _) = self.state
+            && direction == request.direction
+            && self.reopen_for_call(request.floor)
+        {
+            return Ok(self.get_call_outcome(request));
+        }
+
+        if !self.request_buffer.contains(&request) && self.is_queue_full(request.direction) {
+            return Err(ElevatorRequestErr::QueueFull);
+        }
+
+        // a car call may already have queued the same stop
+        self.lit_hall_calls.insert(request);
+
         match self.request_buffer.insert(request) {
-            true => Ok(true),
-            false => Err(ElevatorRequestErr::DUPLICATE),
-            // _ => Err(ElevatorRequestErr::DENIED),
+            true => Ok(self.get_call_outcome(request)),
+            // re-pressing an already lit hall button is not an error
+            false => Ok(CallOutcome {
+                accepted: false,
+                deferred_reason: None,
+                cancelled: false,
+            }),
         }
     }
 
-    pub fn car_call(&mut self, floor: i8) -> Result<bool, ElevatorRequestErr> {
+    // the checks every hall button goes through, priority calls included
+    fn check_hall_call(&self, request: ElevatorRequest) -> Result<(), ElevatorRequestErr> {
+        if !self.is_in_building(request.floor) {
+            return Err(ElevatorRequestErr::DENIED);
+        }
+
+        if !self.serves_floor(request.floor) {
+            return Err(ElevatorRequestErr::OutOfRange);
+        }
+
+        if self.locked_floors.contains(&request.floor) {
+            return Err(ElevatorRequestErr::Locked);
+        }
+
+        if !self.has_direction_beyond(request) {
+            return Err(ElevatorRequestErr::InvalidDirection);
+        }
+
+        Ok(())
+    }
+
+    // hall call that also reports how many passengers are waiting at it,
+    // equally near calls are then served busiest first
+    pub fn hall_call_with_passengers(
+        &mut self,
+        request: ElevatorRequest,
+        passengers: u32,
+    ) -> Result<bool, ElevatorRequestErr> {
+        let accepted = self.hall_call(request)?;
+        // a call served on the spot by reopening the doors has nobody left waiting
+        if self.request_buffer.contains(&request) {
+            self.waiting_passengers.insert(request, passengers);
+        }
+        Ok(accepted)
+    }
+
+    fn get_waiting_passengers(&self, request: &ElevatorRequest) -> u32 {
+        self.waiting_passengers.get(request).copied().unwrap_or(1)
+    }
+
+    pub fn car_call_with_outcome(&mut self, floor: i8) -> Result<CallOutcome, ElevatorRequestErr> {
+        if !self.is_in_building(floor) {
+            return Err(ElevatorRequestErr::DENIED);
+        }
+
+        if !self.serves_floor(floor) {
+            return Err(ElevatorRequestErr::OutOfRange);
+        }
+
+        if self.locked_floors.contains(&floor) {
+            return Err(ElevatorRequestErr::Locked);
+        }
+
+        if self.reopen_for_call(floor) {
+            return Ok(
+                self.get_call_outcome(ElevatorRequest::new(self.get_waiting_direction(), floor))
+            );
+        }
+
+        if self.lit_car_calls.contains(&floor) {
+            self.cancel_car_call(floor);
+            return Ok(CallOutcome {
+                accepted: false,
+                deferred_reason: None,
+                cancelled: true,
+            });
+        }
+
         let request = ElevatorRequest {
             direction: match self.current_floor.cmp(&floor) {
                 Ordering::Greater => ElevatorDirection::DOWN,
@@ -110,10 +528,142 @@ impl Elevator {
             floor,
         };
 
+        // a hall call may already have queued the same stop, the button still lights
+        self.lit_car_calls.insert(floor);
+
         match self.request_buffer.insert(request) {
-            true => Ok(true),
-            false => Err(ElevatorRequestErr::DUPLICATE),
-            // _ => Err(ElevatorRequestErr::DENIED),
+            true => {
+                self.open_car_call_batch();
+                Ok(self.get_call_outcome(request))
+            }
+            false => Ok(CallOutcome {
+                accepted: false,
+                deferred_reason: None,
+                cancelled: false,
+            }),
+        }
+    }
+
+    // pressing a lit car button again cancels it, a hall call to the same stop keeps it queued
+    fn cancel_car_call(&mut self, floor: i8) {
+        self.lit_car_calls.remove(&floor);
+        self.request_buffer
+            .retain(|request| request.floor != floor || self.lit_hall_calls.contains(request));
+        self.retarget_away_from(floor);
+    }
+
+    // a moving car whose target lost its last request heads for the next one instead
+    fn retarget_away_from(&mut self, floor: i8) {
+        let ElevatorState::MOVING(direction) = self.state else {
+            return;
+        };
+
+        if self.target_floor != floor || self.request_buffer.iter().any(|r| r.floor == floor) {
+            return;
+        }
+
+        let next = self
+            .get_target_on_the_way(direction, true)
+            .map(|request| (request, DispatchReason::EnRouteSameDirection))
+            .or_else(|| {
+                self.get_best_target_with_opposite_direction(direction)
+                    .map(|request| (request, DispatchReason::OppositeDirectionExtreme))
+            });
+
+        match next {
+            Some((request, reason)) => {
+                self.target_floor = request.floor;
+                self.last_decision = Some(reason);
+            }
+            // nothing left ahead, stop at the next floor instead of running to the old target
+            None => {
+                let _ = self.stop_at_next_floor();
+            }
+        }
+    }
+
+    // card-access floor, calls to it are refused and queued ones are dropped
+    pub fn lock_floor(&mut self, floor: i8) {
+        self.locked_floors.insert(floor);
+        if self
+            .priority_request
+            .is_some_and(|request| request.floor == floor)
+        {
+            self.priority_request = None;
+        }
+        if self
+            .parking_request
+            .is_some_and(|request| request.floor == floor)
+        {
+            self.parking_request = None;
+        }
+        self.lit_car_calls.remove(&floor);
+        self.lit_hall_calls.retain(|request| request.floor != floor);
+        self.request_buffer.retain(|request| request.floor != floor);
+        self.retarget_away_from(floor);
+    }
+
+    pub fn unlock_floor(&mut self, floor: i8) {
+        self.locked_floors.remove(&floor);
+    }
+
+    fn is_queue_full(&self, direction: ElevatorDirection) -> bool {
+        self.request_capacity
+            .get(&direction)
+            .is_some_and(|capacity| {
+                self.lit_hall_calls
+                    .iter()
+                    .filter(|request| request.direction == direction)
+                    .count()
+                    >= *capacity
+            })
+    }
+
+    // a call for the floor the car is dwelling at is served by restarting the dwell
+    fn reopen_for_call(&mut self, floor: i8) -> bool {
+        let reopens = self.reopen_on_same_floor_call
+            && matches!(self.state, ElevatorState::WAITING(_, _))
+            && self.dwell_timer.is_active()
+            && floor == self.current_floor;
+
+        if reopens {
+            self.dwell_timer.start(self.get_dwell_time());
+            self.update_doors();
+        }
+
+        reopens
+    }
+
+    fn get_waiting_direction(&self) -> ElevatorDirection {
+        match self.state {
+            ElevatorState::WAITING(direction, _) | ElevatorState::MOVING(direction) => direction,
+            _ => ElevatorDirection::UP,
+        }
+    }
+
+    // the first car call opens the window, later ones join it without extending it
+    fn open_car_call_batch(&mut self) {
+        if let Some(window) = self.car_call_batch_window
+            && !self.car_call_batch.is_active()
+        {
+            self.car_call_batch.start(window);
+        }
+    }
+
+    fn get_call_outcome(&self, request: ElevatorRequest) -> CallOutcome {
+        let deferred_reason = match self.state {
+            ElevatorState::INSPECTION => Some(DeferredReason::Inspection),
+            _ => None,
+        };
+
+        if let Some(reason) = deferred_reason {
+            log::warn!("request {:?} queued but deferred: {:?}", request, reason);
+        }
+
+        CallOutcome {
+            accepted: true,
+            deferred_reason,
+            cancelled: false,
         }
     }
 
@@ -122,9 +672,16 @@ impl Elevator {
         direction: ElevatorDirection,
         is_at_target: bool,
     ) -> Option<ElevatorRequest> {
+        let cluster = self.get_call_cluster(self.target_floor);
+
         self.request_buffer
             .iter()
-            .filter(|request| request.direction == direction)
+            .filter(|request| {
+                request.direction == direction
+                    || cluster
+                        .as_ref()
+                        .is_some_and(|cluster| cluster.contains(&request.floor))
+            })
             .filter(|request| match self.current_floor.cmp(&request.floor) {
                 Ordering::Equal => true,
                 Ordering::Less => {
@@ -136,10 +693,61 @@ impl Elevator {
                         && (is_at_target || request.floor >= self.target_floor)
                 }
             })
-            .min_by_key(|request| (self.current_floor - request.floor).abs())
+            // ties go to the travel direction, then the lower floor, not to HashSet order
+            .min_by_key(|request| {
+                (
+                    self.current_floor.abs_diff(request.floor),
+                    request.direction != direction,
+                    request.floor,
+                )
+            })
+            .copied()
+    }
+
+    fn get_nearest_behind(&self, direction: ElevatorDirection) -> Option<ElevatorRequest> {
+        self.request_buffer
+            .iter()
+            .filter(|request| match direction {
+                ElevatorDirection::UP => request.floor < self.current_floor,
+                ElevatorDirection::DOWN => request.floor > self.current_floor,
+            })
+            // ties go to calls for the reversed direction, then the lower floor
+            .min_by_key(|request| {
+                (
+                    self.current_floor.abs_diff(request.floor),
+                    request.direction == direction,
+                    request.floor,
+                )
+            })
             .copied()
     }
 
+    // floors chained to `floor` by requests at most `coalescing_radius` apart,
+    // requests in the cluster are served in one sweep whatever their direction
+    fn get_call_cluster(&self, floor: i8) -> Option<RangeInclusive<i8>> {
+        let radius = self.coalescing_radius?;
+        let (mut low, mut high) = (floor, floor);
+
+        loop {
+            let mut grown = false;
+
+            for request in &self.request_buffer {
+                if request.floor < low && low.abs_diff(request.floor) <= radius {
+                    low = request.floor;
+                    grown = true;
+                }
+                if request.floor > high && high.abs_diff(request.floor) <= radius {
+                    high = request.floor;
+                    grown = true;
+                }
+            }
+
+            if !grown {
+                return Some(low..=high);
+            }
+        }
+    }
+
     fn get_first_target_in_direction(
         &self,
         direction: ElevatorDirection,
@@ -180,103 +788,563 @@ impl Elevator {
         }
     }
 
-    fn get_next_request_on_idle(&self) -> Option<ElevatorRequest> {
-        self.get_first_target_in_direction(ElevatorDirection::UP)
-            .or_else(|| self.get_first_target_in_direction(ElevatorDirection::DOWN))
+    fn get_nearest_request(&self) -> Option<ElevatorRequest> {
+        self.request_buffer
+            .iter()
+            .min_by_key(|request| {
+                (
+                    self.current_floor.abs_diff(request.floor),
+                    Reverse(self.get_waiting_passengers(request)),
+                    request.floor,
+                    request.direction == ElevatorDirection::DOWN,
+                )
+            })
+            .copied()
+    }
+
+    fn get_next_request_on_idle(&self) -> Option<(ElevatorRequest, DispatchReason)> {
+        let first_up = || {
+            self.get_first_target_in_direction(ElevatorDirection::UP)
+                .map(|request| (request, DispatchReason::IdleFirstUp))
+        };
+        let first_down = || {
+            self.get_first_target_in_direction(ElevatorDirection::DOWN)
+                .map(|request| (request, DispatchReason::IdleFirstDown))
+        };
+
+        match self.idle_priority {
+            IdlePriority::PreferUp => first_up().or_else(first_down),
+            IdlePriority::PreferDown => first_down().or_else(first_up),
+            IdlePriority::PreferNearest => self
+                .get_nearest_request()
+                .map(|request| (request, DispatchReason::IdleNearest)),
+        }
     }
 
     fn get_next_request_while_moving(
         &self,
         direction: ElevatorDirection,
-    ) -> Option<ElevatorRequest> {
-        self.get_target_on_the_way(direction, false)
-            .or_else(|| self.get_best_target_with_opposite_direction(direction))
+    ) -> Option<(ElevatorRequest, DispatchReason)> {
+        let ahead = self.get_target_on_the_way(direction, false);
+
+        if let (Some(ahead), Some(inertia)) = (ahead, self.direction_inertia)
+            && let Some(behind) = self.get_nearest_behind(direction)
+            && self.current_floor.abs_diff(ahead.floor)
+                > self
+                    .current_floor
+                    .abs_diff(behind.floor)
+                    .saturating_add(inertia)
+        {
+            return Some((behind, DispatchReason::CloserReversal));
+        }
+
+        ahead
+            .map(|request| (request, DispatchReason::EnRouteSameDirection))
+            .or_else(|| {
+                self.get_best_target_with_opposite_direction(direction)
+                    .map(|request| (request, DispatchReason::OppositeDirectionExtreme))
+            })
     }
 
     fn get_next_request_after_waiting(
         &self,
         direction: ElevatorDirection,
-    ) -> Option<ElevatorRequest> {
+    ) -> Option<(ElevatorRequest, DispatchReason)> {
         self.get_target_on_the_way(direction, true)
-            .or_else(|| self.get_first_target_in_direction(direction.opposite()))
-            .or_else(|| self.get_first_target_in_direction(direction))
+            .map(|request| (request, DispatchReason::EnRouteSameDirection))
+            .or_else(|| {
+                self.get_first_target_in_direction(direction.opposite())
+                    .map(|request| (request, DispatchReason::OppositeDirectionFirst))
+            })
+            .or_else(|| {
+                self.get_first_target_in_direction(direction)
+                    .map(|request| (request, DispatchReason::SameDirectionFirst))
+            })
     }
 
+    // a request stored for the other direction is served as well. with nothing left
+    // ahead the car turns around here, so it continues in the passenger's direction
     fn remove_finished_request(&mut self, direction: ElevatorDirection) {
-        let _ = self
-            .request_buffer
-            .remove(&ElevatorRequest::new(direction, self.current_floor))
-            || self.request_buffer.remove(&ElevatorRequest::new(
-                direction.opposite(),
-                self.current_floor,
-            ));
+        if self.remove_request(&ElevatorRequest::new(direction, self.current_floor)) {
+            return;
+        }
+
+        let opposite = direction.opposite();
+        if self.remove_request(&ElevatorRequest::new(opposite, self.current_floor))
+            && !self.has_requests_ahead(direction)
+            && let ElevatorState::WAITING(waiting_direction, _) = &mut self.state
+        {
+            *waiting_direction = opposite;
+        }
+    }
+
+    fn has_requests_ahead(&self, direction: ElevatorDirection) -> bool {
+        self.request_buffer.iter().any(|request| match direction {
+            ElevatorDirection::UP => request.floor > self.current_floor,
+            ElevatorDirection::DOWN => request.floor < self.current_floor,
+        })
+    }
+
+    // serving a request also turns off the buttons that asked for it
+    fn remove_request(&mut self, request: &ElevatorRequest) -> bool {
+        if self.priority_request.as_ref() == Some(request) {
+            self.priority_request = None;
+        }
+        self.lit_hall_calls.remove(request);
+        self.lit_car_calls.remove(&request.floor);
+        self.waiting_passengers.remove(request);
+        self.request_buffer.remove(request)
     }
 
     pub fn state_loop(&mut self, dt: f64) {
+        let from = self.state.clone();
+        self.update_state(dt);
+        self.log_transition(&from, "state loop");
+        self.advance_travel(dt);
+
+        #[cfg(feature = "debug-invariants")]
+        self.enforce_invariants();
+    }
+
+    // logic-only travel, one arrival every `seconds_per_floor` while moving
+    fn advance_travel(&mut self, dt: f64) {
+        let Some(seconds_per_floor) = self.seconds_per_floor else {
+            return;
+        };
+
+        if !matches!(self.state, ElevatorState::MOVING(_)) {
+            self.travel_timer.reset();
+            return;
+        }
+
+        if !self.travel_timer.is_active() {
+            self.travel_timer.start(seconds_per_floor);
+        }
+
+        self.travel_timer.tick(dt);
+
+        if self.travel_timer.is_elapsed() {
+            self.travel_timer.reset();
+            self.advance_one_floor();
+        }
+    }
+
+    // moves a MOVING car one floor towards its target, the step shared by the timed
+    // travel, the plan dry run and the logic-only simulation
+    pub(crate) fn advance_one_floor(&mut self) {
+        if !matches!(self.state, ElevatorState::MOVING(_)) {
+            return;
+        }
+
+        let next_floor = match self.current_floor.cmp(&self.target_floor) {
+            Ordering::Less => self.current_floor + 1,
+            Ordering::Greater => self.current_floor - 1,
+            Ordering::Equal => self.current_floor,
+        };
+        let _ = self.notify_reached_floor(next_floor);
+    }
+
+    #[cfg(feature = "debug-invariants")]
+    pub fn set_panic_on_invariant_violation(&mut self, panic: bool) {
+        self.panic_on_invariant_violation = panic;
+    }
+
+    #[cfg(feature = "debug-invariants")]
+    pub fn check_invariants(&self) -> Vec<InvariantViolation> {
+        let mut violations = Vec::new();
+
+        if self.state != ElevatorState::IDLE && !self.serves_floor(self.target_floor) {
+            violations.push(InvariantViolation::TargetOutsideServiceZone(
+                self.target_floor,
+            ));
+        }
+
+        if let ElevatorState::MOVING(_) = self.state {
+            let justified = self
+                .request_buffer
+                .iter()
+                .any(|request| request.floor == self.target_floor);
+
+            if !justified {
+                violations.push(InvariantViolation::MovingWithoutRequest(self.target_floor));
+            }
+        }
+
+        let elapsed = self.dwell_timer.elapsed();
+        if !(0.0..=self.get_dwell_time()).contains(&elapsed) {
+            violations.push(InvariantViolation::DwellOutOfRange(elapsed));
+        }
+
+        violations
+    }
+
+    #[cfg(feature = "debug-invariants")]
+    fn enforce_invariants(&self) {
+        let violations = self.check_invariants();
+
+        for violation in &violations {
+            log::error!("elevator invariant violated: {:?}", violation);
+        }
+
+        if self.panic_on_invariant_violation && !violations.is_empty() {
+            panic!("elevator invariants violated: {:?}", violations);
+        }
+    }
+
+    fn update_state(&mut self, dt: f64) {
+        self.car_call_batch.tick(dt);
+        if self.car_call_batch.is_elapsed() {
+            self.car_call_batch.reset();
+        }
+
+        // the plan is held while car calls are still being collected
+        let batching = self.car_call_batch.is_active();
+
+        if let Some(request) = self.priority_request
+            && matches!(self.state, ElevatorState::IDLE | ElevatorState::MOVING(_))
+        {
+            self.head_to_priority_request(request);
+            return;
+        }
+
         match &self.state {
+            ElevatorState::IDLE if batching => {}
             ElevatorState::IDLE => {
-                if let Some(request) = self.get_next_request_on_idle() {
+                if let Some((request, reason)) = self.get_next_request_on_idle() {
                     self.target_floor = request.floor;
+                    self.last_decision = Some(reason);
 
                     if self.current_floor == request.floor {
-                        self.request_buffer.remove(&request);
+                        self.remove_request(&request);
                         self.state =
                             ElevatorState::WAITING(request.direction, ElevatorDoorsState::CLOSED);
+                        self.at_origin = true;
 
                         return;
                     }
 
                     self.state =
                         ElevatorState::MOVING(request.recalculate_direction(self.current_floor));
+                    self.committed_floor = self.current_floor;
+                } else {
+                    self.park_when_idle(dt);
                 }
             }
-            // todo, implement doors
-            #[allow(unused_variables)]
-            ElevatorState::WAITING(direction, doors) => {
+            ElevatorState::WAITING(direction, _) => {
                 let direction = *direction;
-                if self.waiting_time == 0.0 {
+                // an elapsed timer that is held (batching, scripted hold) is not restarted
+                if self.dwell_timer == DwellTimer::Inactive {
+                    self.door_cycle_count += 1;
                     self.remove_finished_request(direction);
+                    self.dwell_timer.start(self.get_dwell_time());
+                    self.load_settled_time = 0.0;
                 }
 
-                // todo better timer at one point, not a priority
-                self.waiting_time += dt;
+                self.dwell_timer.tick(dt);
+                self.load_settled_time += dt;
+
+                // a settled load starts the door close early
+                if let Some(adaptive) = self.adaptive_dwell
+                    && !self.extended_dwell
+                    && self.dwell_timer.remaining() > self.door_close_time
+                    && self.dwell_timer.elapsed() + self.door_close_time
+                        >= self.get_minimum_dwell_time(adaptive)
+                    && self.load_settled_time >= adaptive.settle_time
+                {
+                    self.dwell_timer
+                        .set_duration(self.dwell_timer.elapsed() + self.door_close_time);
+                }
+                self.update_doors();
 
                 // After waiting period completes
-                if self.waiting_time >= 5.0 {
-                    self.waiting_time = 0.0;
-
-                    if let Some(request) = self.get_next_request_after_waiting(direction) {
+                if self.dwell_timer.is_elapsed() && !batching && !self.is_held() {
+                    self.dwell_timer.reset();
+                    self.extended_dwell = false;
+                    let direction = self.get_departure_direction(direction);
+                    self.at_origin = false;
+
+                    if let Some(request) = self.priority_request {
+                        self.head_to_priority_request(request);
+                    } else if let Some((request, reason)) =
+                        self.get_next_request_after_waiting(direction)
+                    {
                         self.target_floor = request.floor;
+                        self.last_decision = Some(reason);
                         self.state = ElevatorState::MOVING(
                             request.recalculate_direction(self.current_floor),
                         );
+                        self.committed_floor = self.current_floor;
                     } else {
                         self.state = ElevatorState::IDLE;
                     }
                 }
             }
+            ElevatorState::MOVING(_) if batching => {}
             ElevatorState::MOVING(direction) => {
                 if self.target_floor == self.current_floor {
                     // return self.state =
                     // ElevatorState::WAITING(*direction, ElevatorDoorsState::CLOSED);
                 }
 
-                if let Some(request) = self.get_next_request_while_moving(*direction) {
+                if let Some((request, reason)) = self.get_next_request_while_moving(*direction) {
+                    if self.holds_committed_direction(*direction, request.floor) {
+                        return;
+                    }
+
                     self.target_floor = request.floor;
+                    self.last_decision = Some(reason);
+
+                    if reason == DispatchReason::CloserReversal {
+                        self.state = ElevatorState::MOVING(
+                            request.recalculate_direction(self.current_floor),
+                        );
+                        self.committed_floor = self.current_floor;
+                    }
                 } else {
-                    // this should never happen, because:
-                    // the request that put the elevator in the moving mode should still be in the buffer.
-                    panic!();
-                    // todo: we can possibly put the state to idle instead of panicking.
-                    // let's leave it for now so we can see if this ever happens.
+                    // the request that started the move is gone, stop here rather than
+                    // run to a target nobody wants
+                    let direction = *direction;
+                    log::warn!(
+                        "moving {:?} towards {} without a request, stopping at {}",
+                        direction,
+                        self.target_floor,
+                        self.current_floor
+                    );
+                    self.target_floor = self.current_floor;
+                    self.state = match self.request_buffer.is_empty() {
+                        true => ElevatorState::IDLE,
+                        false => ElevatorState::WAITING(direction, ElevatorDoorsState::CLOSED),
+                    };
                 }
             }
+            // the technician drives the car, buffered calls are held until exit
+            ElevatorState::INSPECTION => {}
         }
     }
 
-    pub fn set_current_floor(&mut self, floor: i8) {
-        self.current_floor = floor
-    }
+    // nobody has ridden yet when the doors opened from idle, so the side with more car
+    // calls goes first instead of the hall call's direction
+    fn get_departure_direction(&self, direction: ElevatorDirection) -> ElevatorDirection {
+        if !self.origin_majority || !self.at_origin {
+            return direction;
+        }
+
+        let above = self
+            .lit_car_calls
+            .iter()
+            .filter(|floor| **floor > self.current_floor)
+            .count();
+        let below = self
+            .lit_car_calls
+            .iter()
+            .filter(|floor| **floor < self.current_floor)
+            .count();
+
+        match above.cmp(&below) {
+            Ordering::Greater => ElevatorDirection::UP,
+            Ordering::Less => ElevatorDirection::DOWN,
+            Ordering::Equal => direction,
+        }
+    }
+
+    fn get_parking_floor(&self) -> i8 {
+        let (low, high) = self.get_served_ends();
+        if low > high {
+            return self.current_floor;
+        }
+
+        match self.parking_policy {
+            ParkingPolicy::LastStop => self.current_floor,
+            ParkingPolicy::Lobby => 0.clamp(low, high),
+            ParkingPolicy::Middle => ((low as i16 + high as i16) / 2) as i8,
+        }
+    }
+
+    // the parking trip is an ordinary request, so any call that arrives meanwhile is
+    // dispatched the usual way. the doors only open at the parking floor for a call
+    fn park_when_idle(&mut self, dt: f64) {
+        let floor = self.get_parking_floor();
+        if floor == self.current_floor || self.locked_floors.contains(&floor) {
+            self.idle_timer.reset();
+            return;
+        }
+
+        if !self.idle_timer.is_active() {
+            self.idle_timer.start(self.parking_timeout);
+        }
+        self.idle_timer.tick(dt);
+
+        if self.idle_timer.is_elapsed() {
+            self.idle_timer.reset();
+
+            let direction = match floor > self.current_floor {
+                true => ElevatorDirection::UP,
+                false => ElevatorDirection::DOWN,
+            };
+            let request = ElevatorRequest::new(direction, floor);
+            self.request_buffer.insert(request);
+            self.parking_request = Some(request);
+            self.target_floor = floor;
+            self.last_decision = Some(DispatchReason::Parking);
+            self.committed_floor = self.current_floor;
+            self.state = ElevatorState::MOVING(direction);
+        }
+    }
+
+    pub fn set_parking_policy(&mut self, policy: ParkingPolicy) {
+        self.parking_policy = policy;
+        self.idle_timer.reset();
+    }
+
+    pub fn set_parking_timeout(&mut self, timeout: f64) {
+        self.parking_timeout = timeout;
+    }
+
+    // a target behind the furthest floor reached is a reversal, it is only taken
+    // once the current target no longer has a request behind it
+    fn holds_committed_direction(&self, direction: ElevatorDirection, floor: i8) -> bool {
+        if !self.direction_hysteresis {
+            return false;
+        }
+
+        let reverses = match direction {
+            ElevatorDirection::UP => floor < self.committed_floor,
+            ElevatorDirection::DOWN => floor > self.committed_floor,
+        };
+
+        reverses
+            && self
+                .request_buffer
+                .iter()
+                .any(|request| request.floor == self.target_floor)
+    }
+
+    pub fn hold_until(&mut self, floor: i8, condition: Box<dyn FnMut() -> bool>) {
+        self.floor_hold = Some(FloorHold {
+            floor,
+            condition: Callback(Rc::new(RefCell::new(condition))),
+        });
+    }
+
+    // polls the hold condition, the hold is dropped once it is met
+    fn is_held(&mut self) -> bool {
+        let Some(hold) = self.floor_hold.as_mut() else {
+            return false;
+        };
+
+        if hold.floor != self.current_floor {
+            return false;
+        }
+
+        if (hold.condition.0.borrow_mut())() {
+            self.floor_hold = None;
+            return false;
+        }
+
+        true
+    }
+
+    // a priority call ignores the usual direction logic, the car reverses if it has to
+    fn head_to_priority_request(&mut self, request: ElevatorRequest) {
+        self.target_floor = request.floor;
+        self.last_decision = Some(DispatchReason::PriorityCall);
+
+        if self.current_floor == request.floor {
+            self.remove_request(&request);
+            self.state = ElevatorState::WAITING(request.direction, ElevatorDoorsState::CLOSED);
+            return;
+        }
+
+        let direction = request.recalculate_direction(self.current_floor);
+        if self.state != ElevatorState::MOVING(direction) {
+            self.committed_floor = self.current_floor;
+            self.state = ElevatorState::MOVING(direction);
+        }
+    }
+
+    // keyed call (firefighter, medical) served before every other request
+    pub fn priority_hall_call(
+        &mut self,
+        request: ElevatorRequest,
+    ) -> Result<bool, ElevatorRequestErr> {
+        self.check_hall_call(request)?;
+
+        self.request_buffer.insert(request);
+        self.lit_hall_calls.insert(request);
+        self.priority_request = Some(request);
+        Ok(true)
+    }
+
+    pub fn enter_inspection(&mut self) {
+        self.dwell_timer.reset();
+        self.extended_dwell = false;
+        self.target_floor = self.current_floor;
+        self.state = ElevatorState::INSPECTION;
+    }
+
+    pub fn exit_inspection(&mut self) {
+        if self.state == ElevatorState::INSPECTION {
+            self.state = ElevatorState::IDLE;
+        }
+    }
+
+    pub fn inspect_goto(&mut self, floor: i8) -> Result<(), ElevatorRequestErr> {
+        if self.state != ElevatorState::INSPECTION {
+            return Err(ElevatorRequestErr::DENIED);
+        }
+
+        if !self.serves_floor(floor) {
+            return Err(ElevatorRequestErr::OutOfRange);
+        }
+
+        self.target_floor = floor;
+        Ok(())
+    }
+
+    // soft stop, the rest of the plan is kept and resumed after the stop
+    pub fn stop_at_next_floor(&mut self) -> Result<(), ElevatorRequestErr> {
+        let ElevatorState::MOVING(direction) = self.state else {
+            return Err(ElevatorRequestErr::DENIED);
+        };
+
+        let next_floor = match direction {
+            ElevatorDirection::UP => self.current_floor.checked_add(1),
+            ElevatorDirection::DOWN => self.current_floor.checked_sub(1),
+        };
+
+        match next_floor {
+            Some(floor) if self.serves_floor(floor) => {
+                self.request_buffer
+                    .insert(ElevatorRequest::new(direction, floor));
+                self.target_floor = floor;
+                Ok(())
+            }
+            _ => Err(ElevatorRequestErr::OutOfRange),
+        }
+    }
+
+    pub fn set_current_floor(&mut self, floor: i8) {
+        self.current_floor = floor
+    }
+
+    // resync with the controller's floor estimate, a moving car is treated as arriving
+    pub fn force_set_current_floor(&mut self, floor: i8) {
+        if floor != self.current_floor {
+            log::info!(
+                "resyncing current floor {} -> {} in {:?}",
+                self.current_floor,
+                floor,
+                self.state
+            );
+        }
+
+        match self.state {
+            ElevatorState::MOVING(_) => {
+                let _ = self.notify_reached_floor(floor);
+            }
+            _ => self.current_floor = floor,
+        }
+    }
 
     pub fn get_target_floor(&self) -> i8 {
         self.target_floor
@@ -290,33 +1358,376 @@ impl Elevator {
         &self.state
     }
 
-    pub fn get_all_requests(&self) -> hash_set::Iter<ElevatorRequest> {
+    pub fn get_all_requests(&self) -> hash_set::Iter<'_, ElevatorRequest> {
         self.request_buffer.iter()
     }
 
+    // by floor, UP before DOWN
+    pub fn get_all_requests_sorted(&self) -> Vec<ElevatorRequest> {
+        let mut requests: Vec<_> = self.request_buffer.iter().copied().collect();
+        requests
+            .sort_by_key(|request| (request.floor, request.direction == ElevatorDirection::DOWN));
+        requests
+    }
+
+    pub fn lit_buttons(&self) -> Vec<(i8, ElevatorDirection)> {
+        let mut buttons: Vec<_> = self
+            .lit_hall_calls
+            .iter()
+            .map(|request| (request.floor, request.direction))
+            .collect();
+        buttons.sort_by_key(|(floor, direction)| (*floor, *direction == ElevatorDirection::DOWN));
+        buttons
+    }
+
+    pub fn lit_car_calls(&self) -> Vec<i8> {
+        let mut floors: Vec<_> = self.lit_car_calls.iter().copied().collect();
+        floors.sort();
+        floors
+    }
+
+    // multi-line summary for bug reports, requests are sorted so the output is stable
+    pub fn debug_dump(&self) -> String {
+        let requests = self.get_all_requests_sorted();
+
+        let mut dump = String::new();
+        dump.push_str(&format!("state: {:?}\n", self.state));
+        dump.push_str(&format!("current floor: {}\n", self.current_floor));
+        dump.push_str(&format!("target floor: {}\n", self.target_floor));
+        dump.push_str(&format!("last decision: {:?}\n", self.last_decision));
+        dump.push_str(&format!("dwell timer: {:?}\n", self.dwell_timer));
+        dump.push_str(&format!("service zone: {:?}\n", self.service_zone));
+        dump.push_str(&format!("priority request: {:?}\n", self.priority_request));
+        dump.push_str(&format!("lit hall buttons: {:?}\n", self.lit_buttons()));
+        dump.push_str(&format!("lit car buttons: {:?}\n", self.lit_car_calls()));
+        dump.push_str(&format!("requests ({}):\n", requests.len()));
+        for request in requests {
+            dump.push_str(&format!("  - {:?} {}\n", request.direction, request.floor));
+        }
+
+        dump
+    }
+
     pub fn notify_reached_floor(&mut self, reached_floor: i8) -> Result<(), ElevatorFloorReachErr> {
+        let from = self.state.clone();
+        let result = self.apply_reached_floor(reached_floor);
+        self.log_transition(&from, "floor reached");
+        result
+    }
+
+    // audit trail of state changes at debug level, off by default to avoid log spam
+    pub fn set_log_transitions(&mut self, enabled: bool) {
+        self.log_transitions = enabled;
+    }
+
+    fn log_transition(&self, from: &ElevatorState, trigger: &str) {
+        if self.log_transitions && *from != self.state {
+            log::debug!(
+                "{:?} -> {:?} ({}), floor {} target {}, decision {:?}",
+                from,
+                self.state,
+                trigger,
+                self.current_floor,
+                self.target_floor,
+                self.last_decision
+            );
+        }
+    }
+
+    fn apply_reached_floor(&mut self, reached_floor: i8) -> Result<(), ElevatorFloorReachErr> {
         match self.state {
             ElevatorState::MOVING(direction) => {
                 self.current_floor = reached_floor;
+                self.committed_floor = match direction {
+                    ElevatorDirection::UP => self.committed_floor.max(reached_floor),
+                    ElevatorDirection::DOWN => self.committed_floor.min(reached_floor),
+                };
 
                 if self.current_floor == self.target_floor {
+                    if self.finish_parking() {
+                        return Ok(());
+                    }
+
                     self.state = ElevatorState::WAITING(direction, ElevatorDoorsState::CLOSED);
+                    self.count_trip();
                 }
 
                 Ok(())
             }
+            ElevatorState::INSPECTION => {
+                self.current_floor = reached_floor;
+                Ok(())
+            }
+            // repeated notification for the arrival we are already serving
+            ElevatorState::WAITING(_, _) if reached_floor == self.current_floor => Ok(()),
             _ => Err(ElevatorFloorReachErr::NotMoving),
         }
     }
 
+    // arrived at the parking floor with nobody calling there, the car idles with the
+    // doors closed
+    fn finish_parking(&mut self) -> bool {
+        let Some(parking) = self.parking_request else {
+            return false;
+        };
+        if parking.floor != self.current_floor {
+            return false;
+        }
+        self.parking_request = None;
+
+        let called = self.lit_car_calls.contains(&self.current_floor)
+            || self
+                .request_buffer
+                .iter()
+                .any(|request| request.floor == self.current_floor && *request != parking);
+        if called {
+            return false;
+        }
+
+        self.remove_request(&parking);
+        self.state = ElevatorState::IDLE;
+        true
+    }
+
+    fn count_trip(&mut self) {
+        self.trip_count += 1;
+
+        if let Some(alert) = self.maintenance_alert.as_mut()
+            && self.trip_count == alert.threshold
+        {
+            log::warn!("maintenance due after {} trips", self.trip_count);
+            (alert.on_due.0.borrow_mut())();
+        }
+    }
+
+    pub fn set_maintenance_alert(&mut self, threshold: u64, on_due: Box<dyn FnMut()>) {
+        self.maintenance_alert = Some(MaintenanceAlert {
+            threshold,
+            on_due: Callback(Rc::new(RefCell::new(on_due))),
+        });
+    }
+
+    pub fn get_trip_count(&self) -> u64 {
+        self.trip_count
+    }
+
+    pub fn get_door_cycle_count(&self) -> u64 {
+        self.door_cycle_count
+    }
+
     pub fn get_waiting_time(&self) -> f64 {
-        self.waiting_time
+        self.dwell_timer.elapsed()
+    }
+
+    // caps the outstanding hall calls per direction, None is unlimited. car calls are
+    // neither capped nor counted
+    pub fn set_request_capacity(&mut self, direction: ElevatorDirection, capacity: Option<usize>) {
+        match capacity {
+            Some(capacity) => self.request_capacity.insert(direction, capacity),
+            None => self.request_capacity.remove(&direction),
+        };
+    }
+
+    pub fn set_idle_priority(&mut self, priority: IdlePriority) {
+        self.idle_priority = priority;
+    }
+
+    // None leaves arrivals to notify_reached_floor
+    pub fn set_seconds_per_floor(&mut self, seconds_per_floor: Option<f64>) {
+        self.seconds_per_floor = seconds_per_floor;
+    }
+
+    pub fn set_coalescing_radius(&mut self, radius: Option<u8>) {
+        self.coalescing_radius = radius;
+    }
+
+    pub fn set_reopen_on_same_floor_call(&mut self, enabled: bool) {
+        self.reopen_on_same_floor_call = enabled;
+    }
+
+    pub fn set_direction_hysteresis(&mut self, enabled: bool) {
+        self.direction_hysteresis = enabled;
+    }
+
+    pub fn set_origin_majority(&mut self, enabled: bool) {
+        self.origin_majority = enabled;
+    }
+
+    // a moving car turns back for a request behind it only when that request is more
+    // than `inertia` floors closer than the next one ahead. None never turns back
+    pub fn set_direction_inertia(&mut self, inertia: Option<u8>) {
+        self.direction_inertia = inertia;
+    }
+
+    pub fn set_car_call_batch_window(&mut self, window: Option<f64>) {
+        self.car_call_batch_window = window;
+    }
+
+    pub fn get_last_decision(&self) -> Option<DispatchReason> {
+        self.last_decision
+    }
+
+    pub fn get_dwell_timer(&self) -> &DwellTimer {
+        &self.dwell_timer
+    }
+
+    // the whole stop at the current floor, the doors close during its last
+    // `door_close_time` seconds
+    fn get_dwell_time(&self) -> f64 {
+        if self.extended_dwell {
+            self.accessible_dwell_time
+        } else if let Some(adaptive) = self.adaptive_dwell {
+            adaptive.max.max(self.get_minimum_dwell_time(adaptive))
+        } else {
+            self.floor_dwell_times
+                .get(&self.current_floor)
+                .copied()
+                .unwrap_or(self.door_open_time + self.door_close_time)
+        }
+    }
+
+    // a per-floor dwell is not cut short by a settled load
+    fn get_minimum_dwell_time(&self, adaptive: AdaptiveDwell) -> f64 {
+        match self.floor_dwell_times.get(&self.current_floor) {
+            Some(dwell_time) => adaptive.min.max(*dwell_time),
+            None => adaptive.min,
+        }
+    }
+
+    fn update_doors(&mut self) {
+        let open =
+            self.dwell_timer.is_active() && self.dwell_timer.remaining() > self.door_close_time;
+
+        if let ElevatorState::WAITING(_, doors) = &mut self.state {
+            *doors = match open {
+                true => ElevatorDoorsState::OPEN,
+                false => ElevatorDoorsState::CLOSED,
+            };
+        }
+    }
+
+    // holds the doors for `accessible_dwell_time` at the current stop only
+    pub fn request_extended_dwell(&mut self) -> bool {
+        if !matches!(self.state, ElevatorState::WAITING(_, _)) {
+            return false;
+        }
+
+        self.extended_dwell = true;
+        self.dwell_timer.set_duration(self.get_dwell_time());
+        self.update_doors();
+        true
+    }
+
+    pub fn set_accessible_dwell_time(&mut self, dwell_time: f64) {
+        self.accessible_dwell_time = dwell_time;
+    }
+
+    pub fn set_adaptive_dwell(&mut self, adaptive_dwell: Option<AdaptiveDwell>) {
+        self.adaptive_dwell = adaptive_dwell;
+    }
+
+    // latest load cell reading, any change restarts the settle time
+    pub fn report_load(&mut self, load: f64) {
+        if (load - self.load).abs() > LOAD_CHANGE_TOLERANCE {
+            self.load_settled_time = 0.0;
+        }
+        self.load = load;
+    }
+
+    // floors without an override use the default dwell. with an adaptive dwell the
+    // override is the shortest stop at that floor
+    pub fn set_floor_dwell_time(&mut self, floor: i8, dwell_time: f64) {
+        self.floor_dwell_times.insert(floor, dwell_time);
+    }
+
+    pub fn set_service_zone(&mut self, zone: RangeInclusive<i8>) {
+        if zone.is_empty() {
+            log::warn!(
+                "service zone {:?} has no floors, every call is rejected",
+                zone
+            );
+        }
+        self.service_zone = zone;
+    }
+
+    // no UP call from the top served floor, no DOWN call from the bottom one, whether
+    // the end comes from the building or the service zone. with a single floor either
+    // button just opens the doors there
+    fn has_direction_beyond(&self, request: ElevatorRequest) -> bool {
+        let (bottom, top) = self.get_served_ends();
+
+        bottom == top
+            || match request.direction {
+                ElevatorDirection::UP => request.floor < top,
+                ElevatorDirection::DOWN => request.floor > bottom,
+            }
+    }
+
+    // lowest and highest floor that is both in the building and in the service zone
+    fn get_served_ends(&self) -> (i8, i8) {
+        (
+            *self.service_zone.start().max(self.floor_range.start()),
+            *self.service_zone.end().min(self.floor_range.end()),
+        )
+    }
+
+    // DENIED: no such floor. OutOfRange: the floor exists, this car does not serve it
+    fn is_in_building(&self, floor: i8) -> bool {
+        self.floor_range.contains(&floor)
+    }
+
+    pub fn get_floor_range(&self) -> &RangeInclusive<i8> {
+        &self.floor_range
+    }
+
+    pub fn get_service_zone(&self) -> &RangeInclusive<i8> {
+        &self.service_zone
+    }
+
+    pub fn serves_floor(&self, floor: i8) -> bool {
+        self.service_zone.contains(&floor)
     }
 }
 
 #[cfg(test)]
 mod state_tests {
     use super::*;
+    use std::{
+        cell::{Cell, RefCell},
+        rc::Rc,
+        sync::Once,
+    };
+
+    const DWELL_TIME: f64 = DOOR_OPEN_TIME + DOOR_CLOSE_TIME;
+
+    thread_local! {
+        static CAPTURED_LOGS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    }
+
+    // records each test thread's log lines separately, tests run in parallel
+    struct CapturingLogger;
+
+    impl log::Log for CapturingLogger {
+        fn enabled(&self, _: &log::Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &log::Record) {
+            let line = format!("{} {}", record.level(), record.args());
+            CAPTURED_LOGS.with(|logs| logs.borrow_mut().push(line));
+        }
+
+        fn flush(&self) {}
+    }
+
+    fn capture_logs() {
+        static INSTALL: Once = Once::new();
+        INSTALL.call_once(|| {
+            log::set_logger(&CapturingLogger).unwrap();
+            log::set_max_level(log::LevelFilter::Debug);
+        });
+        CAPTURED_LOGS.with(|logs| logs.borrow_mut().clear());
+    }
 
     #[test]
     fn tbw_sceneario() {
@@ -421,7 +1832,7 @@ mod state_tests {
         // Should now be waiting at floor 3
         assert_eq!(
             elevator.state,
-            ElevatorState::WAITING(ElevatorDirection::UP, ElevatorDoorsState::CLOSED),
+            ElevatorState::WAITING(ElevatorDirection::UP, ElevatorDoorsState::OPEN),
             "Elevator should be waiting at floor 3"
         );
 
@@ -461,7 +1872,7 @@ mod state_tests {
         // Should now be waiting at floor 2
         assert_eq!(
             elevator.state,
-            ElevatorState::WAITING(ElevatorDirection::DOWN, ElevatorDoorsState::CLOSED),
+            ElevatorState::WAITING(ElevatorDirection::DOWN, ElevatorDoorsState::OPEN),
             "Elevator should be waiting at floor 2"
         );
 
@@ -503,7 +1914,7 @@ mod state_tests {
         // Wait at floor 3
         assert_eq!(
             elevator.state,
-            ElevatorState::WAITING(ElevatorDirection::UP, ElevatorDoorsState::CLOSED)
+            ElevatorState::WAITING(ElevatorDirection::UP, ElevatorDoorsState::OPEN)
         );
 
         // Next destination
@@ -563,7 +1974,6 @@ mod state_tests {
         println!("{:#?}", elevator.state);
         println!("{:#?}", elevator.target_floor);
 
-        // panics
         simulate_movement(&mut elevator, 10);
 
         // After reaching final destination, should return to IDLE
@@ -688,16 +2098,18 @@ mod state_tests {
         // Duplicate request
         assert_eq!(
             elevator.hall_call(req),
-            Err(ElevatorRequestErr::DUPLICATE),
-            "Elevator should reject duplicate request"
+            Ok(false),
+            "Elevator should treat duplicate hall call as a re-press"
         );
+        assert_eq!(elevator.get_all_requests().count(), 1);
 
-        // Duplicate car call
+        // car call to the already queued stop
         assert_eq!(
             elevator.car_call(7),
-            Err(ElevatorRequestErr::DUPLICATE),
-            "Elevator should reject duplicate car call"
+            Ok(false),
+            "Elevator should treat a car call to a queued stop as already queued"
         );
+        assert_eq!(elevator.get_all_requests().count(), 1);
     }
 
     #[test]
@@ -817,32 +2229,1890 @@ mod state_tests {
     }
 
     #[test]
-    fn test_priority_for_same_direction_requests() {
+    fn test_extreme_floors_do_not_overflow() {
         let mut elevator = Elevator::new();
-        elevator.set_current_floor(5);
+        elevator.set_current_floor(-100);
 
-        // Requests in different directions
         assert_eq!(
-            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::UP, 8)),
+            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::UP, 120)),
             Ok(true)
         );
         assert_eq!(
-            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::DOWN, 7)),
+            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::UP, 110)),
             Ok(true)
         );
 
-        // Should prioritize the UP direction first
         elevator.state_loop(5.1);
         assert_eq!(elevator.state, ElevatorState::MOVING(ElevatorDirection::UP));
-        assert_eq!(elevator.get_target_floor(), 8);
+        assert_eq!(elevator.get_target_floor(), 110);
 
-        // After handling that, should pick up the DOWN request at 7
-        simulate_movement(&mut elevator, 8);
+        // distance from -100 to 110 does not fit in an i8
         elevator.state_loop(5.1);
+        assert_eq!(elevator.get_target_floor(), 110);
+
+        // a closer request en route still wins
         assert_eq!(
-            elevator.state,
-            ElevatorState::MOVING(ElevatorDirection::DOWN)
+            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::UP, 50)),
+            Ok(true)
+        );
+        elevator.state_loop(5.1);
+        assert_eq!(elevator.get_target_floor(), 50);
+    }
+
+    #[test]
+    fn test_service_zone_rejects_other_floors() {
+        let mut low_rise = Elevator::new();
+        low_rise.set_service_zone(0..=10);
+
+        assert_eq!(
+            low_rise.hall_call(ElevatorRequest::new(ElevatorDirection::DOWN, 20)),
+            Err(ElevatorRequestErr::OutOfRange),
+            "low-rise car should refuse a hall call above its zone"
+        );
+        assert_eq!(
+            low_rise.car_call(20),
+            Err(ElevatorRequestErr::OutOfRange),
+            "low-rise car should refuse a car call above its zone"
+        );
+        assert_eq!(low_rise.get_all_requests().count(), 0);
+
+        // zone boundaries are still served
+        assert_eq!(low_rise.car_call(10), Ok(true));
+        assert_eq!(
+            low_rise.hall_call(ElevatorRequest::new(ElevatorDirection::UP, 0)),
+            Ok(true)
+        );
+
+        let mut high_rise = Elevator::new();
+        high_rise.set_service_zone(11..=30);
+        assert_eq!(
+            high_rise.hall_call(ElevatorRequest::new(ElevatorDirection::DOWN, 20)),
+            Ok(true)
+        );
+    }
+
+    #[test]
+    fn test_extended_dwell_applies_to_current_stop_only() {
+        let mut elevator = Elevator::new();
+        elevator.set_current_floor(3);
+        elevator.set_accessible_dwell_time(12.0);
+
+        assert!(
+            !elevator.request_extended_dwell(),
+            "extended dwell is only accepted while waiting"
+        );
+
+        assert_eq!(
+            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::UP, 3)),
+            Ok(true)
+        );
+        assert_eq!(
+            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::UP, 6)),
+            Ok(true)
+        );
+
+        elevator.state_loop(0.1);
+        assert!(matches!(elevator.state, ElevatorState::WAITING(_, _)));
+
+        elevator.state_loop(1.0);
+        assert!(elevator.request_extended_dwell());
+
+        // well past the normal dwell, still holding
+        elevator.state_loop(5.0);
+        assert!(matches!(elevator.state, ElevatorState::WAITING(_, _)));
+
+        elevator.state_loop(6.1);
+        assert_eq!(elevator.state, ElevatorState::MOVING(ElevatorDirection::UP));
+
+        simulate_movement(&mut elevator, 6);
+        assert!(matches!(elevator.state, ElevatorState::WAITING(_, _)));
+
+        // next stop uses the normal dwell again
+        elevator.state_loop(5.1);
+        assert_eq!(elevator.state, ElevatorState::IDLE);
+    }
+
+    #[cfg(feature = "debug-invariants")]
+    #[test]
+    fn test_invariants_detect_corrupted_state() {
+        let mut elevator = Elevator::new();
+        assert!(elevator.check_invariants().is_empty());
+
+        // MOVING with nothing in the buffer to justify it
+        elevator.state = ElevatorState::MOVING(ElevatorDirection::UP);
+        elevator.target_floor = 4;
+        assert_eq!(
+            elevator.check_invariants(),
+            vec![InvariantViolation::MovingWithoutRequest(4)]
+        );
+
+        elevator.set_service_zone(0..=10);
+        elevator.state = ElevatorState::WAITING(ElevatorDirection::UP, ElevatorDoorsState::CLOSED);
+        elevator.target_floor = 20;
+        assert_eq!(
+            elevator.check_invariants(),
+            vec![InvariantViolation::TargetOutsideServiceZone(20)]
+        );
+    }
+
+    #[cfg(feature = "debug-invariants")]
+    #[test]
+    #[should_panic(expected = "elevator invariants violated")]
+    fn test_invariant_violation_panics_when_enabled() {
+        let mut elevator = Elevator::new();
+        elevator.set_panic_on_invariant_violation(true);
+        elevator.set_service_zone(0..=10);
+        elevator.set_current_floor(20);
+        elevator.state = ElevatorState::WAITING(ElevatorDirection::UP, ElevatorDoorsState::CLOSED);
+        elevator.target_floor = 20;
+
+        elevator.state_loop(0.1);
+    }
+
+    #[test]
+    fn test_inspection_mode_holds_pending_calls() {
+        let mut elevator = Elevator::new();
+        elevator.set_current_floor(0);
+
+        assert_eq!(
+            elevator.inspect_goto(3),
+            Err(ElevatorRequestErr::DENIED),
+            "inspect_goto is only allowed in inspection mode"
+        );
+
+        assert_eq!(
+            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::UP, 5)),
+            Ok(true)
+        );
+        assert_eq!(elevator.car_call(8), Ok(true));
+
+        elevator.state_loop(0.1);
+        assert_eq!(elevator.state, ElevatorState::MOVING(ElevatorDirection::UP));
+        assert_eq!(elevator.notify_reached_floor(1), Ok(()));
+        assert_eq!(elevator.notify_reached_floor(2), Ok(()));
+
+        elevator.enter_inspection();
+        assert_eq!(elevator.state, ElevatorState::INSPECTION);
+        assert_eq!(elevator.get_target_floor(), 2);
+
+        // drive down against the pending up calls
+        assert_eq!(elevator.inspect_goto(-1), Ok(()));
+        assert_eq!(elevator.get_target_floor(), -1);
+        for floor in [1, 0, -1] {
+            assert_eq!(elevator.notify_reached_floor(floor), Ok(()));
+            elevator.state_loop(5.1);
+        }
+
+        assert_eq!(elevator.state, ElevatorState::INSPECTION);
+        assert_eq!(elevator.get_current_floor(), -1);
+        assert_eq!(
+            elevator.get_all_requests().count(),
+            2,
+            "buffered calls should be untouched during inspection"
+        );
+
+        elevator.exit_inspection();
+        elevator.state_loop(0.1);
+        assert_eq!(elevator.state, ElevatorState::MOVING(ElevatorDirection::UP));
+        assert_eq!(elevator.get_target_floor(), 5);
+    }
+
+    #[test]
+    fn test_call_deferred_during_inspection() {
+        let mut elevator = Elevator::new();
+        elevator.set_current_floor(2);
+
+        assert_eq!(
+            elevator.car_call_with_outcome(6),
+            Ok(CallOutcome {
+                accepted: true,
+                deferred_reason: None,
+                cancelled: false
+            }),
+            "call is immediately actionable in normal service"
+        );
+
+        elevator.enter_inspection();
+
+        assert_eq!(
+            elevator.hall_call_with_outcome(ElevatorRequest::new(ElevatorDirection::DOWN, 4)),
+            Ok(CallOutcome {
+                accepted: true,
+                deferred_reason: Some(DeferredReason::Inspection),
+                cancelled: false
+            }),
+            "call during inspection is queued but deferred"
+        );
+        assert_eq!(
+            elevator.car_call(7),
+            Ok(true),
+            "plain car_call keeps returning Ok(true)"
+        );
+        assert_eq!(elevator.get_all_requests().count(), 3);
+    }
+
+    #[test]
+    fn test_repeated_arrival_notification_is_noop() {
+        let mut elevator = Elevator::new();
+        elevator.set_current_floor(0);
+        assert_eq!(elevator.car_call(2), Ok(true));
+
+        elevator.state_loop(0.1);
+        assert_eq!(elevator.notify_reached_floor(1), Ok(()));
+        assert_eq!(elevator.notify_reached_floor(2), Ok(()));
+        assert_eq!(
+            elevator.state,
+            ElevatorState::WAITING(ElevatorDirection::UP, ElevatorDoorsState::CLOSED)
+        );
+
+        elevator.state_loop(1.0);
+        let before = format!("{:?}", elevator);
+
+        assert_eq!(
+            elevator.notify_reached_floor(2),
+            Ok(()),
+            "same arrival again should be benign"
+        );
+        assert_eq!(format!("{:?}", elevator), before, "no side effects");
+
+        assert_eq!(
+            elevator.notify_reached_floor(3),
+            Err(ElevatorFloorReachErr::NotMoving),
+            "a different floor while waiting is still an error"
+        );
+    }
+
+    #[test]
+    fn test_dispatch_reason_follows_branch() {
+        let mut elevator = Elevator::new();
+        elevator.set_current_floor(5);
+        assert_eq!(elevator.get_last_decision(), None);
+
+        assert_eq!(
+            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::UP, 8)),
+            Ok(true)
+        );
+        assert_eq!(
+            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::DOWN, 2)),
+            Ok(true)
+        );
+
+        elevator.state_loop(0.1);
+        assert_eq!(
+            elevator.get_last_decision(),
+            Some(DispatchReason::IdleFirstUp)
+        );
+
+        elevator.state_loop(0.1);
+        assert_eq!(
+            elevator.get_last_decision(),
+            Some(DispatchReason::EnRouteSameDirection)
+        );
+
+        simulate_movement(&mut elevator, 8);
+        elevator.state_loop(5.1);
+        assert_eq!(elevator.get_target_floor(), 2);
+        assert_eq!(
+            elevator.get_last_decision(),
+            Some(DispatchReason::OppositeDirectionFirst)
+        );
+    }
+
+    #[test]
+    fn test_idle_priority_prefer_nearest() {
+        let mut elevator = Elevator::new();
+        elevator.set_current_floor(5);
+        elevator.set_idle_priority(IdlePriority::PreferNearest);
+
+        assert_eq!(
+            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::UP, 12)),
+            Ok(true)
+        );
+        assert_eq!(
+            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::DOWN, 4)),
+            Ok(true)
+        );
+
+        elevator.state_loop(0.1);
+        assert_eq!(
+            elevator.state,
+            ElevatorState::MOVING(ElevatorDirection::DOWN),
+            "closer DOWN request should win over the UP one"
+        );
+        assert_eq!(elevator.get_target_floor(), 4);
+        assert_eq!(
+            elevator.get_last_decision(),
+            Some(DispatchReason::IdleNearest)
+        );
+    }
+
+    #[test]
+    fn test_idle_priority_prefer_down() {
+        let mut elevator = Elevator::new();
+        elevator.set_current_floor(5);
+        elevator.set_idle_priority(IdlePriority::PreferDown);
+
+        assert_eq!(
+            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::UP, 6)),
+            Ok(true)
+        );
+        assert_eq!(
+            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::DOWN, 1)),
+            Ok(true)
+        );
+
+        elevator.state_loop(0.1);
+        assert_eq!(elevator.get_target_floor(), 1);
+    }
+
+    #[test]
+    fn test_priority_for_same_direction_requests() {
+        let mut elevator = Elevator::new();
+        elevator.set_current_floor(5);
+
+        // Requests in different directions
+        assert_eq!(
+            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::UP, 8)),
+            Ok(true)
+        );
+        assert_eq!(
+            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::DOWN, 7)),
+            Ok(true)
+        );
+
+        // Should prioritize the UP direction first
+        elevator.state_loop(5.1);
+        assert_eq!(elevator.state, ElevatorState::MOVING(ElevatorDirection::UP));
+        assert_eq!(elevator.get_target_floor(), 8);
+
+        // After handling that, should pick up the DOWN request at 7
+        simulate_movement(&mut elevator, 8);
+        elevator.state_loop(5.1);
+        assert_eq!(
+            elevator.state,
+            ElevatorState::MOVING(ElevatorDirection::DOWN)
+        );
+        assert_eq!(elevator.get_target_floor(), 7);
+    }
+
+    #[test]
+    fn test_car_calls_within_batch_window_are_planned_together() {
+        let mut elevator = Elevator::new();
+        elevator.set_current_floor(0);
+        elevator.set_car_call_batch_window(Some(1.0));
+
+        assert_eq!(elevator.car_call(8), Ok(true));
+        elevator.state_loop(0.3);
+        assert_eq!(elevator.car_call(5), Ok(true));
+        elevator.state_loop(0.3);
+        assert_eq!(elevator.car_call(2), Ok(true));
+        elevator.state_loop(0.3);
+
+        assert_eq!(
+            elevator.state,
+            ElevatorState::IDLE,
+            "no plan while the window is open"
+        );
+        assert_eq!(elevator.get_last_decision(), None);
+
+        elevator.state_loop(0.3);
+        assert_eq!(elevator.state, ElevatorState::MOVING(ElevatorDirection::UP));
+        assert_eq!(
+            elevator.get_target_floor(),
+            2,
+            "plan should cover all three calls"
+        );
+
+        elevator.state_loop(0.1);
+        assert_eq!(elevator.get_target_floor(), 2, "no reordering afterwards");
+    }
+
+    #[test]
+    fn test_origin_car_calls_choose_the_busier_side() {
+        let mut elevator = Elevator::new();
+        elevator.set_current_floor(5);
+        elevator.set_origin_majority(true);
+
+        assert_eq!(
+            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::DOWN, 5)),
+            Ok(true)
+        );
+        elevator.state_loop(0.1);
+        assert_eq!(
+            elevator.state,
+            ElevatorState::WAITING(ElevatorDirection::DOWN, ElevatorDoorsState::CLOSED)
+        );
+
+        assert_eq!(elevator.car_call(2), Ok(true));
+        elevator.state_loop(1.0);
+        assert_eq!(elevator.car_call(7), Ok(true));
+        assert_eq!(elevator.car_call(9), Ok(true));
+        elevator.state_loop(DWELL_TIME);
+
+        assert_eq!(
+            elevator.state,
+            ElevatorState::MOVING(ElevatorDirection::UP),
+            "two destinations above outweigh one below"
+        );
+        assert_eq!(elevator.get_target_floor(), 7);
+
+        simulate_movement(&mut elevator, 7);
+        elevator.state_loop(DWELL_TIME + 0.1);
+        simulate_movement(&mut elevator, 9);
+        elevator.state_loop(DWELL_TIME + 0.1);
+        assert_eq!(
+            elevator.state,
+            ElevatorState::MOVING(ElevatorDirection::DOWN)
+        );
+        assert_eq!(elevator.get_target_floor(), 2, "floor 2 is served last");
+    }
+
+    #[test]
+    fn test_dwell_time_per_floor() {
+        let mut elevator = Elevator::new();
+        elevator.set_current_floor(1);
+        elevator.set_floor_dwell_time(0, 12.0);
+        elevator.set_floor_dwell_time(3, 2.0);
+
+        assert_eq!(elevator.car_call(0), Ok(true));
+        elevator.state_loop(0.1);
+        simulate_movement(&mut elevator, 0);
+        elevator.state_loop(11.0);
+        assert!(
+            matches!(elevator.state, ElevatorState::WAITING(_, _)),
+            "lobby dwell is longer"
+        );
+        elevator.state_loop(1.1);
+        assert_eq!(elevator.state, ElevatorState::IDLE);
+
+        assert_eq!(elevator.car_call(3), Ok(true));
+        elevator.state_loop(0.1);
+        simulate_movement(&mut elevator, 3);
+        elevator.state_loop(2.1);
+        assert_eq!(
+            elevator.state,
+            ElevatorState::IDLE,
+            "upper floor dwell is shorter"
+        );
+
+        assert_eq!(elevator.car_call(5), Ok(true));
+        elevator.state_loop(0.1);
+        simulate_movement(&mut elevator, 5);
+        elevator.state_loop(4.0);
+        assert!(
+            matches!(elevator.state, ElevatorState::WAITING(_, _)),
+            "unlisted floor keeps the default dwell"
+        );
+    }
+
+    #[test]
+    fn test_hall_call_repress_is_noop() {
+        let mut elevator = Elevator::new();
+        elevator.set_current_floor(0);
+
+        let req = ElevatorRequest::new(ElevatorDirection::DOWN, 4);
+        assert_eq!(elevator.hall_call(req), Ok(true));
+        elevator.state_loop(0.1);
+        let before = format!("{:?}", elevator);
+
+        assert_eq!(
+            elevator.hall_call(req),
+            Ok(false),
+            "re-press is not an error"
+        );
+        assert_eq!(
+            format!("{:?}", elevator),
+            before,
+            "re-press changes nothing"
+        );
+
+        assert_eq!(
+            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::UP, 4)),
+            Ok(true),
+            "the other direction at the same floor is a new call"
+        );
+        elevator.set_service_zone(0..=10);
+        assert_eq!(
+            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::UP, 12)),
+            Err(ElevatorRequestErr::OutOfRange),
+            "conflicting inputs are still errors"
+        );
+    }
+
+    #[test]
+    fn test_stop_at_next_floor() {
+        let mut elevator = Elevator::new();
+        elevator.set_current_floor(0);
+        assert_eq!(
+            elevator.stop_at_next_floor(),
+            Err(ElevatorRequestErr::DENIED),
+            "only a moving car can soft stop"
+        );
+
+        assert_eq!(elevator.car_call(5), Ok(true));
+        elevator.state_loop(0.1);
+        assert_eq!(elevator.notify_reached_floor(1), Ok(()));
+
+        assert_eq!(elevator.stop_at_next_floor(), Ok(()));
+        elevator.state_loop(0.1);
+        assert_eq!(elevator.get_target_floor(), 2);
+
+        assert_eq!(elevator.notify_reached_floor(2), Ok(()));
+        assert_eq!(
+            elevator.state,
+            ElevatorState::WAITING(ElevatorDirection::UP, ElevatorDoorsState::CLOSED),
+            "car should stop at the next floor"
+        );
+
+        elevator.state_loop(5.1);
+        assert_eq!(
+            elevator.state,
+            ElevatorState::MOVING(ElevatorDirection::UP),
+            "car should resume its itinerary"
+        );
+        assert_eq!(elevator.get_target_floor(), 5);
+        assert_eq!(elevator.get_all_requests().count(), 1);
+    }
+
+    #[test]
+    fn test_direction_hysteresis_ignores_floor_jitter() {
+        let moving_up_from_five = |hysteresis: bool| {
+            let mut elevator = Elevator::new();
+            elevator.set_current_floor(5);
+            elevator.set_direction_hysteresis(hysteresis);
+
+            assert_eq!(
+                elevator.hall_call(ElevatorRequest::new(ElevatorDirection::UP, 6)),
+                Ok(true)
+            );
+            elevator.state_loop(0.1);
+            assert_eq!(elevator.get_target_floor(), 6);
+            assert_eq!(
+                elevator.hall_call(ElevatorRequest::new(ElevatorDirection::UP, 4)),
+                Ok(true)
+            );
+            elevator
+        };
+
+        let mut elevator = moving_up_from_five(false);
+        assert_eq!(elevator.notify_reached_floor(3), Ok(()));
+        elevator.state_loop(0.1);
+        assert_eq!(
+            elevator.get_target_floor(),
+            4,
+            "without hysteresis the target follows the noise"
+        );
+
+        let mut elevator = moving_up_from_five(true);
+        for floor in [3, 5, 3, 5, 4] {
+            assert_eq!(elevator.notify_reached_floor(floor), Ok(()));
+            elevator.state_loop(0.1);
+            assert_eq!(
+                elevator.get_target_floor(),
+                6,
+                "committed direction should not flip"
+            );
+            assert_eq!(elevator.state, ElevatorState::MOVING(ElevatorDirection::UP));
+        }
+
+        assert_eq!(elevator.notify_reached_floor(6), Ok(()));
+        assert_eq!(
+            elevator.state,
+            ElevatorState::WAITING(ElevatorDirection::UP, ElevatorDoorsState::CLOSED)
+        );
+    }
+
+    #[test]
+    fn test_logic_only_timed_travel() {
+        let mut elevator = Elevator::new();
+        elevator.set_current_floor(0);
+        elevator.set_seconds_per_floor(Some(2.0));
+        assert_eq!(elevator.car_call(3), Ok(true));
+
+        let dt = 0.5;
+        let mut time = 0.0;
+        let mut arrivals = Vec::new();
+        while time < 20.0 {
+            let previous_floor = elevator.get_current_floor();
+            elevator.state_loop(dt);
+            time += dt;
+
+            if elevator.get_current_floor() != previous_floor {
+                arrivals.push((elevator.get_current_floor(), time));
+            }
+        }
+
+        assert_eq!(
+            arrivals,
+            vec![(1, 2.0), (2, 4.0), (3, 6.0)],
+            "one floor every two seconds"
+        );
+        assert_eq!(elevator.state, ElevatorState::IDLE);
+        assert_eq!(elevator.get_all_requests().count(), 0);
+    }
+
+    #[test]
+    fn test_lit_buttons_by_origin() {
+        let mut elevator = Elevator::new();
+        elevator.set_current_floor(2);
+
+        assert_eq!(
+            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::DOWN, 6)),
+            Ok(true)
+        );
+        assert_eq!(
+            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::UP, 4)),
+            Ok(true)
+        );
+        assert_eq!(elevator.car_call(4), Ok(false), "stop is already queued");
+        assert_eq!(elevator.car_call(5), Ok(true));
+        assert_eq!(elevator.car_call(0), Ok(true));
+
+        assert_eq!(
+            elevator.lit_buttons(),
+            vec![(4, ElevatorDirection::UP), (6, ElevatorDirection::DOWN)]
+        );
+        assert_eq!(elevator.lit_car_calls(), vec![0, 4, 5]);
+
+        elevator.state_loop(0.1);
+        simulate_movement(&mut elevator, 4);
+        elevator.state_loop(0.1);
+        assert_eq!(
+            elevator.lit_buttons(),
+            vec![(6, ElevatorDirection::DOWN)],
+            "served hall button goes off"
+        );
+        assert_eq!(elevator.lit_car_calls(), vec![0, 5]);
+
+        elevator.state_loop(5.0);
+        simulate_movement(&mut elevator, 5);
+        elevator.state_loop(0.1);
+        assert_eq!(
+            elevator.lit_car_calls(),
+            vec![0],
+            "served car button goes off"
+        );
+    }
+
+    #[test]
+    fn test_car_call_to_a_hall_call_stop_lights_the_button() {
+        let mut elevator = Elevator::new();
+        elevator.set_current_floor(2);
+
+        assert_eq!(
+            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::UP, 6)),
+            Ok(true)
+        );
+        assert_eq!(
+            elevator.car_call_with_outcome(6),
+            Ok(CallOutcome {
+                accepted: false,
+                deferred_reason: None,
+                cancelled: false
+            }),
+            "the stop is already queued by the hall call"
+        );
+        assert_eq!(elevator.lit_car_calls(), vec![6]);
+        assert_eq!(elevator.get_all_requests().count(), 1);
+
+        elevator.state_loop(0.1);
+        simulate_movement(&mut elevator, 6);
+        elevator.state_loop(0.1);
+        assert_eq!(elevator.lit_buttons(), vec![]);
+        assert_eq!(elevator.lit_car_calls(), Vec::<i8>::new());
+    }
+
+    #[test]
+    fn test_car_call_double_press_cancels() {
+        let mut elevator = Elevator::new();
+        elevator.set_current_floor(0);
+
+        assert_eq!(elevator.car_call(5), Ok(true));
+        assert_eq!(
+            elevator.car_call_with_outcome(5),
+            Ok(CallOutcome {
+                accepted: false,
+                deferred_reason: None,
+                cancelled: true
+            }),
+            "second press cancels the destination"
+        );
+        assert_eq!(elevator.get_all_requests().count(), 0);
+        assert_eq!(elevator.lit_car_calls(), Vec::<i8>::new());
+
+        // cancelling the current target moves on to the next stop
+        assert_eq!(elevator.car_call(3), Ok(true));
+        assert_eq!(elevator.car_call(6), Ok(true));
+        elevator.state_loop(0.1);
+        assert_eq!(elevator.get_target_floor(), 3);
+        assert_eq!(elevator.notify_reached_floor(1), Ok(()));
+
+        assert_eq!(elevator.car_call(3), Ok(false));
+        assert_eq!(elevator.get_target_floor(), 6);
+        elevator.state_loop(0.1);
+        assert_eq!(elevator.get_target_floor(), 6);
+        assert_eq!(elevator.state, ElevatorState::MOVING(ElevatorDirection::UP));
+
+        // hall calls are not cancelled by the car button
+        assert_eq!(
+            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::DOWN, 8)),
+            Ok(true)
+        );
+        assert_eq!(elevator.car_call(8), Ok(true));
+        assert_eq!(elevator.car_call(8), Ok(false));
+        assert_eq!(
+            elevator.lit_buttons(),
+            vec![(8, ElevatorDirection::DOWN)],
+            "hall button stays lit"
+        );
+        assert_eq!(elevator.get_all_requests().count(), 2);
+    }
+
+    #[test]
+    fn test_adaptive_dwell_waits_for_load_to_settle() {
+        let arrive_at_two = || {
+            let mut elevator = Elevator::new();
+            elevator.set_current_floor(0);
+            elevator.set_adaptive_dwell(Some(AdaptiveDwell {
+                min: 3.0,
+                max: 15.0,
+                settle_time: 2.0,
+            }));
+            assert_eq!(elevator.car_call(2), Ok(true));
+            elevator.state_loop(0.1);
+            simulate_movement(&mut elevator, 2);
+            elevator
+        };
+
+        // nobody boards: closes after the minimum dwell
+        let mut elevator = arrive_at_two();
+        for _ in 0..25 {
+            elevator.state_loop(0.1);
+        }
+        assert!(matches!(elevator.state, ElevatorState::WAITING(_, _)));
+        for _ in 0..5 {
+            elevator.state_loop(0.1);
+        }
+        assert_eq!(elevator.state, ElevatorState::IDLE);
+
+        // passengers board for 8 seconds
+        let mut elevator = arrive_at_two();
+        let mut time = 0.0;
+        while time < 9.5 {
+            if time < 8.0 {
+                elevator.report_load(time * 20.0);
+            }
+            elevator.state_loop(0.1);
+            time += 0.1;
+        }
+        assert!(
+            matches!(elevator.state, ElevatorState::WAITING(_, _)),
+            "dwell extends while the load changes"
+        );
+
+        // the doors start closing once the load is steady
+        for _ in 0..20 {
+            elevator.state_loop(0.1);
+        }
+        assert_eq!(
+            elevator.state,
+            ElevatorState::IDLE,
+            "doors close once the load is steady"
+        );
+    }
+
+    #[test]
+    fn test_adaptive_dwell_keeps_the_floor_dwell() {
+        let mut elevator = Elevator::new();
+        elevator.set_current_floor(0);
+        elevator.set_adaptive_dwell(Some(AdaptiveDwell {
+            min: 3.0,
+            max: 6.0,
+            settle_time: 1.0,
+        }));
+        elevator.set_floor_dwell_time(2, 10.0);
+        assert_eq!(elevator.car_call(2), Ok(true));
+        elevator.state_loop(0.1);
+        simulate_movement(&mut elevator, 2);
+
+        // the load is steady, yet the lobby-style dwell at floor 2 runs in full
+        for _ in 0..95 {
+            elevator.state_loop(0.1);
+        }
+        assert!(
+            matches!(elevator.state, ElevatorState::WAITING(_, _)),
+            "floor dwell outlasts the adaptive maximum"
+        );
+        for _ in 0..10 {
+            elevator.state_loop(0.1);
+        }
+        assert_eq!(elevator.state, ElevatorState::IDLE);
+    }
+
+    #[test]
+    fn test_request_capacity_per_direction() {
+        let mut elevator = Elevator::new();
+        elevator.set_current_floor(0);
+        elevator.set_request_capacity(ElevatorDirection::UP, Some(2));
+
+        // destinations inside the car do not use up the hall call capacity
+        assert_eq!(elevator.car_call(8), Ok(true));
+        assert_eq!(elevator.car_call(9), Ok(true));
+        assert_eq!(
+            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::UP, 3)),
+            Ok(true)
+        );
+        assert_eq!(
+            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::UP, 5)),
+            Ok(true)
+        );
+        assert_eq!(
+            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::UP, 7)),
+            Err(ElevatorRequestErr::QueueFull),
+            "UP queue is at its cap"
+        );
+        assert_eq!(elevator.car_call(10), Ok(true), "car calls are not capped");
+        assert_eq!(
+            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::UP, 5)),
+            Ok(false),
+            "re-press of a queued call is still fine"
+        );
+        assert_eq!(
+            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::DOWN, 7)),
+            Ok(true),
+            "DOWN queue is unlimited"
+        );
+
+        elevator.set_request_capacity(ElevatorDirection::UP, None);
+        assert_eq!(
+            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::UP, 7)),
+            Ok(true)
+        );
+    }
+
+    #[test]
+    fn test_coalesced_calls_served_in_one_sweep() {
+        let stops_with_radius = |radius: Option<u8>| {
+            let mut elevator = Elevator::new();
+            elevator.set_current_floor(0);
+            elevator.set_coalescing_radius(radius);
+
+            for request in [
+                ElevatorRequest::new(ElevatorDirection::DOWN, 4),
+                ElevatorRequest::new(ElevatorDirection::UP, 5),
+                ElevatorRequest::new(ElevatorDirection::DOWN, 6),
+            ] {
+                assert_eq!(elevator.hall_call(request), Ok(true));
+            }
+
+            let mut stops = Vec::new();
+            for _ in 0..100 {
+                elevator.state_loop(0.5);
+
+                if let ElevatorState::MOVING(_) = elevator.state {
+                    let current = elevator.get_current_floor();
+                    let next = match elevator.get_target_floor().cmp(&current) {
+                        Ordering::Greater => current + 1,
+                        _ => current - 1,
+                    };
+                    assert_eq!(elevator.notify_reached_floor(next), Ok(()));
+
+                    if let ElevatorState::WAITING(_, _) = elevator.state {
+                        stops.push(next);
+                    }
+                }
+            }
+            stops
+        };
+
+        assert_eq!(
+            stops_with_radius(None),
+            vec![5, 6, 4],
+            "without coalescing the car reverses"
+        );
+        assert_eq!(
+            stops_with_radius(Some(1)),
+            vec![4, 5, 6],
+            "the cluster should be served in one upward sweep"
+        );
+    }
+
+    #[test]
+    fn test_priority_call_reverses_car() {
+        let mut elevator = Elevator::new();
+        elevator.set_current_floor(2);
+
+        assert_eq!(elevator.car_call(8), Ok(true));
+        assert_eq!(
+            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::UP, 6)),
+            Ok(true)
+        );
+        elevator.state_loop(0.1);
+        assert_eq!(elevator.notify_reached_floor(3), Ok(()));
+        elevator.state_loop(0.1);
+        assert_eq!(elevator.get_target_floor(), 6);
+
+        assert_eq!(
+            elevator.priority_hall_call(ElevatorRequest::new(ElevatorDirection::DOWN, 1)),
+            Ok(true)
+        );
+        elevator.state_loop(0.1);
+        assert_eq!(
+            elevator.state,
+            ElevatorState::MOVING(ElevatorDirection::DOWN),
+            "car should reverse for the priority call"
+        );
+        assert_eq!(elevator.get_target_floor(), 1);
+        assert_eq!(
+            elevator.get_last_decision(),
+            Some(DispatchReason::PriorityCall)
+        );
+
+        simulate_movement(&mut elevator, 1);
+        assert_eq!(
+            elevator.state,
+            ElevatorState::WAITING(ElevatorDirection::DOWN, ElevatorDoorsState::OPEN)
+        );
+
+        elevator.state_loop(5.1);
+        assert_eq!(
+            elevator.state,
+            ElevatorState::MOVING(ElevatorDirection::UP),
+            "normal dispatch resumes after the priority stop"
+        );
+        assert_eq!(elevator.get_target_floor(), 6);
+        assert_eq!(elevator.get_all_requests().count(), 2);
+    }
+
+    #[test]
+    fn test_priority_call_is_validated_like_a_hall_call() {
+        let mut elevator = Elevator::new();
+        elevator.set_service_zone(0..=10);
+        elevator.lock_floor(4);
+
+        let priority = |elevator: &mut Elevator, direction, floor| {
+            elevator.priority_hall_call(ElevatorRequest::new(direction, floor))
+        };
+        assert_eq!(
+            priority(&mut elevator, ElevatorDirection::UP, 4),
+            Err(ElevatorRequestErr::Locked)
+        );
+        assert_eq!(
+            priority(&mut elevator, ElevatorDirection::UP, 10),
+            Err(ElevatorRequestErr::InvalidDirection)
+        );
+        assert_eq!(
+            priority(&mut elevator, ElevatorDirection::DOWN, 12),
+            Err(ElevatorRequestErr::OutOfRange)
+        );
+        assert_eq!(elevator.priority_request, None);
+        assert_eq!(elevator.get_all_requests().count(), 0);
+    }
+
+    #[test]
+    fn test_hold_until_condition() {
+        let released = Rc::new(Cell::new(false));
+        let mut elevator = Elevator::new();
+        elevator.set_current_floor(0);
+
+        let condition = Rc::clone(&released);
+        elevator.hold_until(2, Box::new(move || condition.get()));
+
+        assert_eq!(elevator.car_call(2), Ok(true));
+        assert_eq!(elevator.car_call(4), Ok(true));
+        elevator.state_loop(0.1);
+        simulate_movement(&mut elevator, 2);
+
+        for _ in 0..10 {
+            elevator.state_loop(5.0);
+        }
+        assert_eq!(
+            elevator.state,
+            ElevatorState::WAITING(ElevatorDirection::UP, ElevatorDoorsState::CLOSED),
+            "car holds while the condition is false"
+        );
+
+        released.set(true);
+        elevator.state_loop(0.1);
+        assert_eq!(elevator.state, ElevatorState::MOVING(ElevatorDirection::UP));
+        assert_eq!(elevator.get_target_floor(), 4);
+    }
+
+    #[test]
+    fn test_debug_dump_lists_state() {
+        let mut elevator = Elevator::new();
+        elevator.set_current_floor(1);
+        assert_eq!(elevator.car_call(6), Ok(true));
+        assert_eq!(
+            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::DOWN, 3)),
+            Ok(true)
+        );
+        elevator.state_loop(0.1);
+
+        let dump = elevator.debug_dump();
+        assert!(dump.contains("state: MOVING(UP)"), "{}", dump);
+        assert!(dump.contains("target floor: 6"), "{}", dump);
+        assert!(
+            dump.contains("requests (2):\n  - DOWN 3\n  - UP 6\n"),
+            "requests should be listed in floor order: {}",
+            dump
+        );
+    }
+
+    #[test]
+    fn test_moving_without_request_stops_cleanly() {
+        // the request that started the move is gone, this used to panic
+        let mut elevator =
+            Elevator::from_parts(2, 5, ElevatorState::MOVING(ElevatorDirection::UP), [], 0.0);
+        elevator.state_loop(0.1);
+        assert_eq!(elevator.state, ElevatorState::IDLE);
+        assert_eq!(elevator.get_target_floor(), 2, "the car stays at floor 2");
+    }
+
+    #[test]
+    fn test_calls_outside_the_building_are_denied() {
+        let mut elevator = Elevator::with_config(ElevatorConfig {
+            floor_range: -2..=10,
+            ..ElevatorConfig::default()
+        })
+        .unwrap();
+        assert_eq!(elevator.get_current_floor(), 0);
+
+        assert_eq!(elevator.car_call(-2), Ok(true), "bottom floor");
+        assert_eq!(elevator.car_call(10), Ok(true), "top floor");
+        assert_eq!(elevator.car_call(11), Err(ElevatorRequestErr::DENIED));
+        assert_eq!(elevator.car_call(-40), Err(ElevatorRequestErr::DENIED));
+        assert_eq!(
+            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::DOWN, 120)),
+            Err(ElevatorRequestErr::DENIED)
+        );
+
+        assert_eq!(elevator.car_call(0), Err(ElevatorRequestErr::CurrentFloor));
+        assert_eq!(
+            elevator
+                .car_call_with_outcome(10)
+                .map(|outcome| outcome.cancelled),
+            Ok(true),
+            "lit buttons still toggle"
+        );
+
+        let lobby_start = ElevatorConfig {
+            floor_range: 1..=5,
+            ..ElevatorConfig::default()
+        };
+        assert_eq!(
+            Elevator::with_config(lobby_start.clone()),
+            Err(ElevatorConfigErr::StartOutOfRange(0)),
+            "floor 0 does not exist"
+        );
+        let mut lobby_start = Elevator::with_config(ElevatorConfig {
+            start_floor: 1,
+            ..lobby_start
+        })
+        .unwrap();
+        assert_eq!(lobby_start.get_current_floor(), 1);
+        assert_eq!(lobby_start.car_call(0), Err(ElevatorRequestErr::DENIED));
+
+        assert_eq!(
+            Elevator::with_config(ElevatorConfig {
+                floor_range: RangeInclusive::new(5, 1),
+                ..ElevatorConfig::default()
+            }),
+            Err(ElevatorConfigErr::EmptyFloorRange(5, 1))
+        );
+    }
+
+    #[test]
+    fn test_no_hall_call_beyond_the_end_floors() {
+        let mut elevator = Elevator::with_config(ElevatorConfig {
+            floor_range: 0..=10,
+            ..ElevatorConfig::default()
+        })
+        .unwrap();
+
+        // the same error as for the ends of a service zone
+        assert_eq!(
+            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::UP, 10)),
+            Err(ElevatorRequestErr::InvalidDirection),
+            "nowhere to go up from the top"
+        );
+        assert_eq!(
+            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::DOWN, 0)),
+            Err(ElevatorRequestErr::InvalidDirection),
+            "nowhere to go down from the bottom"
+        );
+        assert_eq!(
+            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::DOWN, 10)),
+            Ok(true)
+        );
+        assert_eq!(
+            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::UP, 0)),
+            Ok(true)
+        );
+        assert_eq!(
+            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::DOWN, 10)),
+            Ok(false),
+            "re-pressing a lit button is still accepted quietly"
+        );
+    }
+
+    #[test]
+    fn test_single_floor_building_only_cycles_doors() {
+        let mut elevator = Elevator::new();
+        elevator.set_service_zone(3..=3);
+        elevator.set_current_floor(3);
+
+        assert_eq!(elevator.car_call(3), Err(ElevatorRequestErr::CurrentFloor));
+        assert_eq!(elevator.car_call(4), Err(ElevatorRequestErr::OutOfRange));
+        assert_eq!(
+            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::UP, 3)),
+            Ok(true),
+            "the only floor's button opens the doors"
+        );
+
+        elevator.state_loop(0.1);
+        assert!(matches!(elevator.state, ElevatorState::WAITING(_, _)));
+        for _ in 0..60 {
+            elevator.state_loop(0.1);
+            assert!(!matches!(elevator.state, ElevatorState::MOVING(_)));
+            assert_eq!(elevator.get_current_floor(), 3);
+        }
+        assert_eq!(elevator.state, ElevatorState::IDLE);
+        assert_eq!(elevator.get_door_cycle_count(), 1);
+    }
+
+    #[test]
+    fn test_sorted_requests() {
+        let mut elevator = Elevator::new();
+        elevator.set_current_floor(4);
+        assert_eq!(elevator.car_call(8), Ok(true));
+        assert_eq!(elevator.car_call(1), Ok(true));
+        assert_eq!(
+            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::DOWN, 6)),
+            Ok(true)
+        );
+        assert_eq!(
+            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::UP, 6)),
+            Ok(true)
+        );
+
+        assert_eq!(
+            elevator.get_all_requests_sorted(),
+            vec![
+                ElevatorRequest::new(ElevatorDirection::DOWN, 1),
+                ElevatorRequest::new(ElevatorDirection::UP, 6),
+                ElevatorRequest::new(ElevatorDirection::DOWN, 6),
+                ElevatorRequest::new(ElevatorDirection::UP, 8),
+            ],
+            "requests should be ordered by floor, then UP before DOWN"
+        );
+    }
+
+    #[test]
+    fn test_same_floor_call_during_dwell_restarts_it() {
+        let dwelling_at_three = |reopen: bool| {
+            let mut elevator = Elevator::new();
+            elevator.set_current_floor(0);
+            elevator.set_reopen_on_same_floor_call(reopen);
+            assert_eq!(elevator.car_call(3), Ok(true));
+            elevator.state_loop(0.1);
+            simulate_movement(&mut elevator, 3);
+            elevator.state_loop(3.0);
+            elevator
+        };
+
+        let mut elevator = dwelling_at_three(true);
+        assert_eq!(
+            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::UP, 3)),
+            Ok(true)
+        );
+        assert_eq!(elevator.get_waiting_time(), 0.0, "dwell restarts");
+        assert_eq!(elevator.get_all_requests().count(), 0, "served on the spot");
+
+        elevator.state_loop(3.0);
+        assert_eq!(elevator.car_call(3), Ok(true), "car button reopens too");
+        elevator.state_loop(4.0);
+        assert!(
+            matches!(elevator.state, ElevatorState::WAITING(_, _)),
+            "dwell extended past the original close time"
+        );
+        elevator.state_loop(1.1);
+        assert_eq!(elevator.state, ElevatorState::IDLE);
+
+        let mut elevator = dwelling_at_three(false);
+        assert_eq!(
+            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::UP, 3)),
+            Ok(true)
+        );
+        assert_eq!(elevator.car_call(3), Err(ElevatorRequestErr::CurrentFloor));
+        assert!(elevator.get_waiting_time() > 3.0, "dwell unaffected");
+        elevator.state_loop(2.1);
+        assert!(
+            !matches!(elevator.state, ElevatorState::WAITING(_, _)),
+            "doors close on time"
+        );
+    }
+
+    #[test]
+    fn test_doors_open_then_close_before_leaving() {
+        let mut elevator = Elevator::with_config(ElevatorConfig {
+            door_open_time: 3.0,
+            door_close_time: 2.0,
+            floor_range: 0..=4,
+            ..ElevatorConfig::default()
+        })
+        .unwrap();
+        assert_eq!(elevator.car_call(2), Ok(true));
+        assert_eq!(elevator.car_call(4), Ok(true));
+        elevator.state_loop(0.1);
+        assert_eq!(elevator.notify_reached_floor(1), Ok(()));
+        assert_eq!(elevator.notify_reached_floor(2), Ok(()));
+        assert_eq!(
+            elevator.state,
+            ElevatorState::WAITING(ElevatorDirection::UP, ElevatorDoorsState::CLOSED),
+            "doors are still shut on arrival"
+        );
+
+        elevator.state_loop(0.1);
+        assert_eq!(
+            elevator.state,
+            ElevatorState::WAITING(ElevatorDirection::UP, ElevatorDoorsState::OPEN)
+        );
+
+        elevator.state_loop(2.9);
+        assert_eq!(
+            elevator.state,
+            ElevatorState::WAITING(ElevatorDirection::UP, ElevatorDoorsState::CLOSED),
+            "doors are closing"
+        );
+
+        elevator.state_loop(1.9);
+        assert_eq!(
+            elevator.state,
+            ElevatorState::WAITING(ElevatorDirection::UP, ElevatorDoorsState::CLOSED),
+            "the car waits for the doors to close"
+        );
+
+        elevator.state_loop(0.2);
+        assert_eq!(elevator.state, ElevatorState::MOVING(ElevatorDirection::UP));
+    }
+
+    #[test]
+    fn test_same_floor_call_keeps_doors_open() {
+        let mut elevator = Elevator::with_config(ElevatorConfig {
+            door_open_time: 3.0,
+            door_close_time: 2.0,
+            ..ElevatorConfig::default()
+        })
+        .unwrap();
+        assert_eq!(elevator.car_call(2), Ok(true));
+        elevator.state_loop(0.1);
+        simulate_movement(&mut elevator, 2);
+
+        elevator.state_loop(2.5);
+        assert_eq!(
+            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::UP, 2)),
+            Ok(true)
+        );
+        elevator.state_loop(2.5);
+        assert_eq!(
+            elevator.state,
+            ElevatorState::WAITING(ElevatorDirection::UP, ElevatorDoorsState::OPEN),
+            "the open time restarts instead of closing"
+        );
+
+        // a call while the doors are closing opens them again
+        elevator.state_loop(1.0);
+        assert_eq!(
+            elevator.state,
+            ElevatorState::WAITING(ElevatorDirection::UP, ElevatorDoorsState::CLOSED)
+        );
+        assert_eq!(elevator.car_call(2), Ok(true));
+        assert_eq!(
+            elevator.state,
+            ElevatorState::WAITING(ElevatorDirection::UP, ElevatorDoorsState::OPEN)
+        );
+
+        elevator.state_loop(4.9);
+        assert!(matches!(elevator.state, ElevatorState::WAITING(_, _)));
+        elevator.state_loop(0.2);
+        assert_eq!(elevator.state, ElevatorState::IDLE);
+    }
+
+    #[test]
+    fn test_door_times_are_validated() {
+        for (open, close) in [(-1.0, 1.0), (4.0, f64::NAN), (f64::INFINITY, 1.0)] {
+            assert!(
+                matches!(
+                    Elevator::with_config(ElevatorConfig {
+                        door_open_time: open,
+                        door_close_time: close,
+                        ..ElevatorConfig::default()
+                    }),
+                    Err(ElevatorConfigErr::InvalidDoorTimes(..))
+                ),
+                "open {} close {}",
+                open,
+                close
+            );
+        }
+        assert_eq!(
+            Elevator::with_config(ElevatorConfig::default()),
+            Ok(Elevator::new())
+        );
+    }
+
+    #[test]
+    fn test_busier_call_served_first_when_equidistant() {
+        let mut elevator = Elevator::new();
+        elevator.set_current_floor(5);
+        elevator.set_idle_priority(IdlePriority::PreferNearest);
+
+        assert_eq!(
+            elevator.hall_call_with_passengers(ElevatorRequest::new(ElevatorDirection::UP, 3), 1),
+            Ok(true)
+        );
+        assert_eq!(
+            elevator.hall_call_with_passengers(ElevatorRequest::new(ElevatorDirection::DOWN, 7), 8),
+            Ok(true)
+        );
+
+        elevator.state_loop(0.1);
+        assert_eq!(
+            elevator.get_target_floor(),
+            7,
+            "eight waiting passengers beat a lone one"
+        );
+        assert_eq!(elevator.state, ElevatorState::MOVING(ElevatorDirection::UP));
+    }
+
+    #[test]
+    fn test_passengers_only_recorded_for_queued_calls() {
+        let mut elevator = Elevator::new();
+        assert_eq!(elevator.car_call(3), Ok(true));
+        elevator.state_loop(0.1);
+        simulate_movement(&mut elevator, 3);
+        elevator.state_loop(1.0);
+
+        // served by reopening the doors, nothing is queued
+        assert_eq!(
+            elevator.hall_call_with_passengers(ElevatorRequest::new(ElevatorDirection::UP, 3), 4),
+            Ok(true)
+        );
+        assert_eq!(elevator.get_all_requests().count(), 0);
+        assert!(elevator.waiting_passengers.is_empty());
+
+        let request = ElevatorRequest::new(ElevatorDirection::DOWN, 6);
+        assert_eq!(elevator.hall_call_with_passengers(request, 2), Ok(true));
+        assert_eq!(elevator.get_waiting_passengers(&request), 2);
+    }
+
+    #[test]
+    fn test_maintenance_due_fires_once() {
+        let mut elevator = Elevator::new();
+        elevator.set_seconds_per_floor(Some(1.0));
+
+        let alerts = Rc::new(Cell::new(0));
+        let counter = Rc::clone(&alerts);
+        elevator.set_maintenance_alert(3, Box::new(move || counter.set(counter.get() + 1)));
+
+        for trip in 1..=5 {
+            let floor = trip % 2;
+            assert_eq!(elevator.car_call(floor), Ok(true));
+            for _ in 0..100 {
+                elevator.state_loop(0.1);
+            }
+            assert_eq!(elevator.get_state(), &ElevatorState::IDLE);
+            assert_eq!(elevator.get_trip_count(), trip as u64);
+            assert_eq!(elevator.get_door_cycle_count(), trip as u64);
+        }
+
+        assert_eq!(alerts.get(), 1, "maintenance due should fire exactly once");
+    }
+
+    #[test]
+    fn test_impossible_hall_direction_rejected() {
+        let mut elevator = Elevator::new();
+        elevator.set_service_zone(0..=10);
+
+        assert_eq!(
+            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::UP, 10)),
+            Err(ElevatorRequestErr::InvalidDirection),
+            "nothing above the top floor"
+        );
+        assert_eq!(
+            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::DOWN, 0)),
+            Err(ElevatorRequestErr::InvalidDirection),
+            "nothing below the bottom floor"
+        );
+        assert_eq!(
+            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::DOWN, 10)),
+            Ok(true)
+        );
+        assert_eq!(elevator.get_all_requests().count(), 1);
+    }
+
+    #[test]
+    fn test_force_set_current_floor_in_idle() {
+        let mut elevator = Elevator::new();
+        assert_eq!(
+            elevator.notify_reached_floor(4),
+            Err(ElevatorFloorReachErr::NotMoving)
+        );
+
+        elevator.force_set_current_floor(4);
+        assert_eq!(elevator.get_current_floor(), 4);
+        assert_eq!(elevator.get_state(), &ElevatorState::IDLE);
+
+        assert_eq!(elevator.car_call(6), Ok(true));
+        elevator.state_loop(0.1);
+        assert_eq!(
+            elevator.get_state(),
+            &ElevatorState::MOVING(ElevatorDirection::UP),
+            "planning starts from the resynced floor"
+        );
+
+        elevator.force_set_current_floor(6);
+        assert_eq!(
+            elevator.get_state(),
+            &ElevatorState::WAITING(ElevatorDirection::UP, ElevatorDoorsState::CLOSED),
+            "a moving car arrives"
+        );
+    }
+
+    #[test]
+    fn test_locked_floor_is_skipped() {
+        let mut elevator = Elevator::new();
+        assert_eq!(elevator.car_call(5), Ok(true));
+        assert_eq!(elevator.car_call(8), Ok(true));
+        elevator.lock_floor(5);
+
+        assert_eq!(elevator.car_call(5), Err(ElevatorRequestErr::Locked));
+        assert_eq!(
+            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::UP, 5)),
+            Err(ElevatorRequestErr::Locked)
+        );
+
+        elevator.set_seconds_per_floor(Some(1.0));
+        let mut stops = Vec::new();
+        for _ in 0..400 {
+            elevator.state_loop(0.1);
+            if matches!(elevator.state, ElevatorState::WAITING(_, _))
+                && stops.last() != Some(&elevator.current_floor)
+            {
+                stops.push(elevator.current_floor);
+            }
+        }
+        assert_eq!(stops, vec![8], "the car passes the locked floor");
+
+        elevator.unlock_floor(5);
+        assert_eq!(elevator.car_call(5), Ok(true));
+    }
+
+    #[test]
+    fn test_locked_floor_drops_priority_and_parking() {
+        let mut elevator = Elevator::new();
+        elevator.set_current_floor(2);
+        assert_eq!(
+            elevator.priority_hall_call(ElevatorRequest::new(ElevatorDirection::DOWN, 6)),
+            Ok(true)
+        );
+        elevator.state_loop(0.1);
+        assert_eq!(elevator.state, ElevatorState::MOVING(ElevatorDirection::UP));
+
+        elevator.lock_floor(6);
+        assert_eq!(elevator.priority_request, None);
+        assert_eq!(elevator.lit_buttons(), vec![]);
+        elevator.state_loop(0.1);
+        assert_ne!(
+            elevator.get_target_floor(),
+            6,
+            "the car no longer heads for the locked floor"
+        );
+
+        let mut parked = Elevator::new();
+        parked.set_service_zone(0..=10);
+        parked.set_current_floor(2);
+        parked.set_parking_policy(ParkingPolicy::Middle);
+        parked.set_parking_timeout(1.0);
+        parked.lock_floor(5);
+        for _ in 0..50 {
+            parked.state_loop(0.1);
+        }
+        assert_eq!(
+            parked.state,
+            ElevatorState::IDLE,
+            "no parking at a locked floor"
+        );
+        assert_eq!(parked.get_all_requests().count(), 0);
+    }
+
+    #[test]
+    fn test_direction_inertia_needs_a_large_lead() {
+        let moving_up_with = |ahead: i8| {
+            let mut elevator = Elevator::from_parts(
+                5,
+                ahead,
+                ElevatorState::MOVING(ElevatorDirection::UP),
+                [
+                    ElevatorRequest::new(ElevatorDirection::UP, ahead),
+                    ElevatorRequest::new(ElevatorDirection::DOWN, 4),
+                ],
+                0.0,
+            );
+            elevator.set_direction_inertia(Some(3));
+            elevator.state_loop(0.1);
+            elevator
+        };
+
+        let small_lead = moving_up_with(8);
+        assert_eq!(
+            small_lead.get_target_floor(),
+            8,
+            "a 2-floor lead is too small"
+        );
+        assert_eq!(
+            small_lead.get_state(),
+            &ElevatorState::MOVING(ElevatorDirection::UP)
+        );
+
+        let large_lead = moving_up_with(12);
+        assert_eq!(large_lead.get_target_floor(), 4, "a 6-floor lead reverses");
+        assert_eq!(
+            large_lead.get_state(),
+            &ElevatorState::MOVING(ElevatorDirection::DOWN)
+        );
+        assert_eq!(
+            large_lead.get_last_decision(),
+            Some(DispatchReason::CloserReversal)
+        );
+    }
+
+    #[test]
+    fn test_equidistant_tie_break_is_stable() {
+        for _ in 0..50 {
+            // every Elevator gets a freshly seeded HashSet
+            let mut elevator = Elevator::from_parts(
+                3,
+                6,
+                ElevatorState::MOVING(ElevatorDirection::UP),
+                [
+                    ElevatorRequest::new(ElevatorDirection::DOWN, 6),
+                    ElevatorRequest::new(ElevatorDirection::UP, 6),
+                ],
+                0.0,
+            );
+            elevator.set_coalescing_radius(Some(1));
+
+            assert_eq!(
+                elevator.get_target_on_the_way(ElevatorDirection::UP, false),
+                Some(ElevatorRequest::new(ElevatorDirection::UP, 6)),
+                "the call in the travel direction wins the tie"
+            );
+        }
+    }
+
+    #[test]
+    fn test_request_served_from_unexpected_side() {
+        // dispatched for an UP call at 6, the car reached it from above after a reversal
+        let mut elevator = Elevator::from_parts(
+            8,
+            6,
+            ElevatorState::MOVING(ElevatorDirection::DOWN),
+            [],
+            0.0,
+        );
+        assert_eq!(
+            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::UP, 6)),
+            Ok(true)
+        );
+        assert_eq!(
+            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::UP, 9)),
+            Ok(true)
+        );
+
+        assert_eq!(elevator.notify_reached_floor(7), Ok(()));
+        assert_eq!(elevator.notify_reached_floor(6), Ok(()));
+        elevator.state_loop(0.1);
+
+        assert_eq!(
+            elevator.get_state(),
+            &ElevatorState::WAITING(ElevatorDirection::UP, ElevatorDoorsState::OPEN),
+            "the car turns around for the passenger going up"
+        );
+        assert!(
+            !elevator
+                .get_all_requests()
+                .any(|request| *request == ElevatorRequest::new(ElevatorDirection::UP, 6)),
+            "the UP call is served"
+        );
+        assert_eq!(elevator.lit_buttons(), vec![(9, ElevatorDirection::UP)]);
+
+        for _ in 0..60 {
+            elevator.state_loop(0.1);
+        }
+        assert_eq!(
+            elevator.get_state(),
+            &ElevatorState::MOVING(ElevatorDirection::UP)
+        );
+        assert_eq!(elevator.get_target_floor(), 9);
+    }
+
+    #[test]
+    fn test_from_parts_moving_with_opposite_call() {
+        let mut elevator = Elevator::from_parts(
+            3,
+            7,
+            ElevatorState::MOVING(ElevatorDirection::UP),
+            [
+                ElevatorRequest::new(ElevatorDirection::UP, 7),
+                ElevatorRequest::new(ElevatorDirection::DOWN, 5),
+            ],
+            0.0,
+        );
+
+        elevator.state_loop(0.1);
+        assert_eq!(
+            elevator.get_target_floor(),
+            7,
+            "the DOWN call at 5 is passed on the way up"
+        );
+
+        let waiting = Elevator::from_parts(
+            7,
+            7,
+            ElevatorState::WAITING(ElevatorDirection::UP, ElevatorDoorsState::CLOSED),
+            [ElevatorRequest::new(ElevatorDirection::DOWN, 5)],
+            4.0,
+        );
+        assert_eq!(waiting.get_waiting_time(), 4.0);
+        assert_eq!(waiting.get_dwell_timer().remaining(), 1.0);
+    }
+
+    #[test]
+    fn test_dry_run_plan_matches_real_run() {
+        let mut elevator = Elevator::new();
+        elevator.set_current_floor(4);
+        assert_eq!(elevator.car_call(7), Ok(true));
+        assert_eq!(elevator.car_call(1), Ok(true));
+        assert_eq!(
+            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::DOWN, 9)),
+            Ok(true)
+        );
+        assert_eq!(
+            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::UP, 5)),
+            Ok(true)
+        );
+
+        let plan = elevator.simulate_plan();
+        assert_eq!(
+            elevator.state,
+            ElevatorState::IDLE,
+            "dry run leaves the car alone"
+        );
+        assert_eq!(elevator.get_all_requests().count(), 4);
+
+        elevator.set_seconds_per_floor(Some(1.0));
+        let mut visited = Vec::new();
+        let mut was_waiting = false;
+        for _ in 0..1000 {
+            elevator.state_loop(0.1);
+            let waiting = matches!(elevator.state, ElevatorState::WAITING(_, _));
+            if waiting && !was_waiting {
+                visited.push(elevator.current_floor);
+            }
+            was_waiting = waiting;
+        }
+
+        assert_eq!(elevator.state, ElevatorState::IDLE);
+        let planned_floors: Vec<_> = plan.iter().map(|(floor, _)| *floor).collect();
+        assert_eq!(planned_floors, vec![5, 7, 9, 1]);
+        assert_eq!(planned_floors, visited);
+        assert_eq!(
+            plan[2],
+            (9, ElevatorDirection::DOWN),
+            "the car turns around at the DOWN call"
+        );
+    }
+
+    #[test]
+    fn test_dry_run_plan_skips_callbacks() {
+        let mut elevator = Elevator::new();
+        let calls = Rc::new(Cell::new(0));
+        let hold_calls = Rc::clone(&calls);
+        elevator.hold_until(
+            3,
+            Box::new(move || {
+                hold_calls.set(hold_calls.get() + 1);
+                false
+            }),
+        );
+        let alert_calls = Rc::clone(&calls);
+        elevator.set_maintenance_alert(1, Box::new(move || alert_calls.set(alert_calls.get() + 1)));
+        assert_eq!(elevator.car_call(3), Ok(true));
+        assert_eq!(elevator.car_call(6), Ok(true));
+
+        let planned_floors: Vec<_> = elevator
+            .simulate_plan()
+            .iter()
+            .map(|(floor, _)| *floor)
+            .collect();
+        assert_eq!(planned_floors, vec![3, 6], "the hold counts as released");
+        assert_eq!(calls.get(), 0, "no callback runs during the dry run");
+        assert!(elevator.floor_hold.is_some() && elevator.maintenance_alert.is_some());
+    }
+
+    #[test]
+    fn test_dry_run_plan_keeps_origin_majority() {
+        let mut elevator = Elevator::new();
+        elevator.set_current_floor(5);
+        elevator.set_origin_majority(true);
+        assert_eq!(
+            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::DOWN, 5)),
+            Ok(true)
+        );
+        elevator.state_loop(0.1);
+        assert_eq!(elevator.car_call(2), Ok(true));
+        assert_eq!(elevator.car_call(7), Ok(true));
+        assert_eq!(elevator.car_call(9), Ok(true));
+
+        let planned_floors: Vec<_> = elevator
+            .simulate_plan()
+            .iter()
+            .map(|(floor, _)| *floor)
+            .collect();
+        assert_eq!(
+            planned_floors,
+            vec![7, 9, 2],
+            "the plan leaves towards the busier side like the car does"
+        );
+    }
+
+    #[test]
+    fn test_middle_parking_after_idle_timeout() {
+        let mut elevator = Elevator::new();
+        elevator.set_service_zone(0..=10);
+        elevator.set_current_floor(2);
+        elevator.set_seconds_per_floor(Some(1.0));
+        elevator.set_parking_policy(ParkingPolicy::Middle);
+        elevator.set_parking_timeout(10.0);
+
+        for _ in 0..95 {
+            elevator.state_loop(0.1);
+        }
+        assert_eq!(
+            elevator.state,
+            ElevatorState::IDLE,
+            "still inside the timeout"
+        );
+
+        for _ in 0..10 {
+            elevator.state_loop(0.1);
+        }
+        assert_eq!(elevator.state, ElevatorState::MOVING(ElevatorDirection::UP));
+        assert_eq!(elevator.get_target_floor(), 5);
+
+        for _ in 0..200 {
+            elevator.state_loop(0.1);
+        }
+        assert_eq!(elevator.get_current_floor(), 5);
+        assert_eq!(elevator.state, ElevatorState::IDLE, "parked cars stay put");
+
+        let mut last_stop = Elevator::new();
+        last_stop.set_current_floor(2);
+        for _ in 0..1000 {
+            last_stop.state_loop(0.1);
+        }
+        assert_eq!(last_stop.state, ElevatorState::IDLE);
+        assert_eq!(
+            last_stop.get_current_floor(),
+            2,
+            "default keeps the last stop"
+        );
+    }
+
+    #[test]
+    fn test_lobby_parking_keeps_the_doors_closed() {
+        let mut elevator = Elevator::new();
+        elevator.set_current_floor(4);
+        elevator.set_seconds_per_floor(Some(1.0));
+        elevator.set_parking_policy(ParkingPolicy::Lobby);
+        elevator.set_parking_timeout(1.0);
+
+        for _ in 0..100 {
+            elevator.state_loop(0.1);
+            assert!(
+                !matches!(elevator.state, ElevatorState::WAITING(_, _)),
+                "parking does not open the doors"
+            );
+        }
+        assert_eq!(elevator.get_current_floor(), 0, "the lobby is floor 0");
+        assert_eq!(elevator.state, ElevatorState::IDLE);
+        assert_eq!(elevator.get_door_cycle_count(), 0);
+        assert_eq!(elevator.get_all_requests().count(), 0);
+
+        // without a floor 0 the car parks at the served floor nearest to it
+        let mut high_rise = Elevator::with_config(ElevatorConfig {
+            floor_range: 2..=8,
+            start_floor: 6,
+            ..ElevatorConfig::default()
+        })
+        .unwrap();
+        high_rise.set_seconds_per_floor(Some(1.0));
+        high_rise.set_parking_policy(ParkingPolicy::Lobby);
+        high_rise.set_parking_timeout(1.0);
+        for _ in 0..15 {
+            high_rise.state_loop(0.1);
+        }
+        assert_eq!(
+            high_rise.state,
+            ElevatorState::MOVING(ElevatorDirection::DOWN)
+        );
+        assert_eq!(high_rise.get_target_floor(), 2);
+
+        // a call at the parking floor while on the way still opens the doors there
+        assert_eq!(
+            high_rise.hall_call(ElevatorRequest::new(ElevatorDirection::UP, 2)),
+            Ok(true)
+        );
+        for _ in 0..100 {
+            high_rise.state_loop(0.1);
+        }
+        assert_eq!(high_rise.get_current_floor(), 2);
+        assert_eq!(high_rise.get_door_cycle_count(), 1);
+    }
+
+    #[test]
+    fn test_transitions_are_logged() {
+        capture_logs();
+
+        let mut elevator = Elevator::new();
+        assert_eq!(elevator.car_call(1), Ok(true));
+        elevator.state_loop(0.1);
+        assert_eq!(elevator.notify_reached_floor(1), Ok(()));
+        let quiet = CAPTURED_LOGS.with(|logs| logs.borrow().len());
+        assert_eq!(quiet, 0, "transitions are not logged by default");
+
+        elevator.set_log_transitions(true);
+        assert_eq!(elevator.car_call(2), Ok(true));
+        for _ in 0..60 {
+            elevator.state_loop(0.1);
+        }
+        assert_eq!(elevator.notify_reached_floor(2), Ok(()));
+
+        let logs = CAPTURED_LOGS.with(|logs| logs.borrow().clone());
+        assert!(
+            logs.iter().any(|line| line.starts_with("DEBUG")
+                && line.contains("MOVING(UP) -> WAITING(UP, CLOSED)")
+                && line.contains("floor 2 target 2")),
+            "arrival should be logged: {:?}",
+            logs
+        );
+        assert!(
+            logs.iter()
+                .any(|line| line.contains("WAITING(UP, CLOSED) -> MOVING(UP)")),
+            "departure should be logged: {:?}",
+            logs
         );
-        assert_eq!(elevator.get_target_floor(), 7);
     }
 }
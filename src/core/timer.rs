@@ -0,0 +1,129 @@
+#[derive(PartialEq, Clone, Copy, Debug, Default)]
+pub enum DwellTimer {
+    #[default]
+    Inactive,
+    Active {
+        elapsed: f64,
+        duration: f64,
+    },
+    Elapsed {
+        duration: f64,
+    },
+}
+
+impl DwellTimer {
+    pub fn start(&mut self, duration: f64) {
+        *self = DwellTimer::Active {
+            elapsed: 0.0,
+            duration,
+        };
+    }
+
+    pub fn tick(&mut self, dt: f64) {
+        if let DwellTimer::Active { elapsed, duration } = *self {
+            let elapsed = elapsed + dt;
+
+            *self = if elapsed >= duration {
+                DwellTimer::Elapsed { duration }
+            } else {
+                DwellTimer::Active { elapsed, duration }
+            };
+        }
+    }
+
+    // changes the target of a running timer, keeping the time already elapsed
+    pub fn set_duration(&mut self, new_duration: f64) {
+        if let DwellTimer::Active { duration, .. } = self {
+            *duration = new_duration;
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = DwellTimer::Inactive;
+    }
+
+    pub fn is_active(&self) -> bool {
+        matches!(self, DwellTimer::Active { .. })
+    }
+
+    pub fn is_elapsed(&self) -> bool {
+        matches!(self, DwellTimer::Elapsed { .. })
+    }
+
+    pub fn elapsed(&self) -> f64 {
+        match self {
+            DwellTimer::Inactive => 0.0,
+            DwellTimer::Active { elapsed, .. } => *elapsed,
+            DwellTimer::Elapsed { duration } => *duration,
+        }
+    }
+
+    pub fn remaining(&self) -> f64 {
+        match self {
+            DwellTimer::Active { elapsed, duration } => duration - elapsed,
+            _ => 0.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod timer_tests {
+    use super::*;
+
+    #[test]
+    fn test_start() {
+        let mut timer = DwellTimer::default();
+        assert_eq!(timer, DwellTimer::Inactive);
+        assert_eq!(timer.remaining(), 0.0);
+
+        timer.start(5.0);
+        assert!(timer.is_active());
+        assert_eq!(timer.elapsed(), 0.0);
+        assert_eq!(timer.remaining(), 5.0);
+    }
+
+    #[test]
+    fn test_partial_elapse() {
+        let mut timer = DwellTimer::default();
+        timer.start(5.0);
+        timer.tick(2.0);
+
+        assert!(timer.is_active(), "timer should still be running");
+        assert_eq!(timer.elapsed(), 2.0);
+        assert_eq!(timer.remaining(), 3.0);
+    }
+
+    #[test]
+    fn test_completion() {
+        let mut timer = DwellTimer::default();
+        timer.start(5.0);
+        timer.tick(3.0);
+        timer.tick(2.5);
+
+        assert!(timer.is_elapsed(), "timer should have elapsed");
+        assert_eq!(timer.remaining(), 0.0);
+        assert_eq!(timer.elapsed(), 5.0);
+
+        // ticking an elapsed or inactive timer does nothing
+        timer.tick(1.0);
+        assert!(timer.is_elapsed());
+
+        timer.reset();
+        timer.tick(1.0);
+        assert_eq!(timer, DwellTimer::Inactive);
+    }
+
+    #[test]
+    fn test_set_duration_keeps_elapsed() {
+        let mut timer = DwellTimer::default();
+        timer.start(5.0);
+        timer.tick(4.0);
+        timer.set_duration(10.0);
+
+        assert_eq!(timer.elapsed(), 4.0);
+        assert_eq!(timer.remaining(), 6.0);
+
+        timer.tick(2.0);
+        assert!(timer.is_active(), "longer duration should keep it running");
+    }
+}
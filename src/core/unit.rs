@@ -0,0 +1,118 @@
+use crate::core::{Elevator, ElevatorRequest, ElevatorRequestErr, ElevatorState};
+
+// common interface so different car implementations can share a bank
+pub trait ElevatorUnit {
+    fn hall_call(&mut self, request: ElevatorRequest) -> Result<bool, ElevatorRequestErr>;
+    fn car_call(&mut self, floor: i8) -> Result<bool, ElevatorRequestErr>;
+    fn state_loop(&mut self, dt: f64);
+    fn get_current_floor(&self) -> i8;
+    fn get_target_floor(&self) -> i8;
+    fn get_state(&self) -> &ElevatorState;
+    fn has_request(&self, request: ElevatorRequest) -> bool;
+    fn get_request_count(&self) -> usize;
+}
+
+impl ElevatorUnit for Elevator {
+    fn hall_call(&mut self, request: ElevatorRequest) -> Result<bool, ElevatorRequestErr> {
+        Elevator::hall_call(self, request)
+    }
+
+    fn car_call(&mut self, floor: i8) -> Result<bool, ElevatorRequestErr> {
+        Elevator::car_call(self, floor)
+    }
+
+    fn state_loop(&mut self, dt: f64) {
+        Elevator::state_loop(self, dt)
+    }
+
+    fn get_current_floor(&self) -> i8 {
+        Elevator::get_current_floor(self)
+    }
+
+    fn get_target_floor(&self) -> i8 {
+        Elevator::get_target_floor(self)
+    }
+
+    fn get_state(&self) -> &ElevatorState {
+        Elevator::get_state(self)
+    }
+
+    fn has_request(&self, request: ElevatorRequest) -> bool {
+        self.get_all_requests().any(|queued| *queued == request)
+    }
+
+    fn get_request_count(&self) -> usize {
+        self.get_all_requests().count()
+    }
+}
+
+// lets a bank mix implementations as Box<dyn ElevatorUnit>
+impl<U: ElevatorUnit + ?Sized> ElevatorUnit for Box<U> {
+    fn hall_call(&mut self, request: ElevatorRequest) -> Result<bool, ElevatorRequestErr> {
+        (**self).hall_call(request)
+    }
+
+    fn car_call(&mut self, floor: i8) -> Result<bool, ElevatorRequestErr> {
+        (**self).car_call(floor)
+    }
+
+    fn state_loop(&mut self, dt: f64) {
+        (**self).state_loop(dt)
+    }
+
+    fn get_current_floor(&self) -> i8 {
+        (**self).get_current_floor()
+    }
+
+    fn get_target_floor(&self) -> i8 {
+        (**self).get_target_floor()
+    }
+
+    fn get_state(&self) -> &ElevatorState {
+        (**self).get_state()
+    }
+
+    fn has_request(&self, request: ElevatorRequest) -> bool {
+        (**self).has_request(request)
+    }
+
+    fn get_request_count(&self) -> usize {
+        (**self).get_request_count()
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use crate::GroupController;
+    use crate::core::ElevatorDirection;
+
+    #[test]
+    fn test_dispatch_through_trait_objects() {
+        let mut low_rise = Elevator::new();
+        low_rise.set_service_zone(0..=10);
+        let mut high_rise = Elevator::new();
+        high_rise.set_current_floor(11);
+        high_rise.set_service_zone(11..=30);
+
+        let units: Vec<Box<dyn ElevatorUnit>> = vec![Box::new(low_rise), Box::new(high_rise)];
+        let mut group = GroupController::new(units);
+
+        let request = ElevatorRequest::new(ElevatorDirection::DOWN, 20);
+        assert_eq!(
+            group.dispatch(request),
+            Ok(1),
+            "only the high-rise car serves floor 20"
+        );
+        group.state_loop(0.1);
+
+        let units = group.get_cars();
+        assert_eq!(*units[0].get_state(), ElevatorState::IDLE);
+        assert_eq!(
+            *units[1].get_state(),
+            ElevatorState::MOVING(ElevatorDirection::UP)
+        );
+        assert_eq!(units[1].get_target_floor(), 20);
+        assert_eq!(units[1].get_current_floor(), 11);
+    }
+}
@@ -1,111 +1,196 @@
-use std::{cell::RefCell, rc::Rc, sync::mpsc, thread, time::Duration};
+use std::{sync::mpsc, thread, time::Duration};
 
 use chrono::Local;
 use elevator_sim::{
-    Elevator, ElevatorController, ElevatorDirection, ElevatorPIDFFController, ElevatorPhysics,
-    ElevatorRequest, Encoder, FeedForward, PIDController, SimulatedEncoder, SimulatedMotor, ui,
+    ConsoleIndicatorSink, DisplayUnits, Elevator, ElevatorDirection, ElevatorPIDFFController,
+    ElevatorPhysics, ElevatorRequest, FeedForward, IndicatorSink, Indicators, LogFormat,
+    MotionConstraints, PIDController, Simulation, UserCommand, ui,
 };
 use fern::Dispatch;
 
 const TIME_STEP: f32 = 1.0 / 60.0;
 
-pub enum UserCommand {
-    HallCall(ElevatorRequest),
-    CarCall(i8),
-    Quit,
-}
-
-fn setup_logger() -> Result<(), Box<dyn std::error::Error>> {
-    Dispatch::new()
-        .format(|out, message, record| {
-            out.finish(format_args!(
-                "[{}][{}][{}] {}",
-                Local::now().format("%Y-%m-%d %H:%M:%S"),
-                record.level(),
-                record.target(),
-                message
-            ))
+fn setup_logger(rpc_mode: bool, log_format: LogFormat) -> Result<(), Box<dyn std::error::Error>> {
+    let dispatch = Dispatch::new()
+        .format(move |out, message, record| {
+            let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+            match log_format {
+                LogFormat::Text => out.finish(format_args!(
+                    "[{}][{}][{}] {}",
+                    timestamp,
+                    record.level(),
+                    record.target(),
+                    message
+                )),
+                LogFormat::Json => out.finish(format_args!(
+                    "{}",
+                    ui::format_json_record(&timestamp, record)
+                )),
+            }
         })
         .level(log::LevelFilter::Debug)
-        .chain(std::fs::File::create("temp.log")?)
-        .chain(std::io::stdout())
-        .apply()?;
+        .chain(std::fs::File::create("temp.log")?);
+
+    // stdout carries the JSON-RPC responses in rpc mode
+    match rpc_mode {
+        true => dispatch.apply()?,
+        false => dispatch.chain(std::io::stdout()).apply()?,
+    }
 
     Ok(())
 }
 
-fn main() {
-    setup_logger().expect("failed");
-    print!("\x1B[2J\x1B[1;1H");
-    println!("elevator-sim.");
-    let encoder = Rc::new(RefCell::new(SimulatedEncoder::new(0.0)));
-    let motor = Rc::new(RefCell::new(SimulatedMotor::new()));
-
-    let mut physics = ElevatorPhysics::new(100.0, 1.0, 3.0, 3.10, -9.81, 100.0);
-
-    let mut elevator = Elevator::new();
-
-    let mut elevator_controller = ElevatorPIDFFController::new(
-        Rc::clone(&encoder),
-        Rc::clone(&motor),
-        12.0,
-        PIDController::new(5.0, 0.0, 20.0),
-        FeedForward::new(10.0, 0.0, 0.0),
-        5.0,
-        0.1,
-    );
-
-    // get elevator calls using mpsc::channel
-    let (input_tx, input_rx) = mpsc::channel::<UserCommand>();
-
-    let input_thread = thread::spawn(move || {
-        loop {
-            let mut input = String::new();
-            std::io::stdin().read_line(&mut input).unwrap();
-            let parts: Vec<&str> = input.split_whitespace().collect();
+fn read_text_commands(input_tx: mpsc::Sender<UserCommand>) {
+    loop {
+        let mut input = String::new();
+        std::io::stdin().read_line(&mut input).unwrap();
+        let parts: Vec<&str> = input.split_whitespace().collect();
 
-            if parts.is_empty() {
-                continue;
-            }
+        if parts.is_empty() {
+            continue;
+        }
 
-            match parts[0] {
-                "h" => {
-                    if parts.len() != 3 {
-                        println!("Usage: h <floor> <u|d>");
-                        continue;
-                    }
-                    let floor: i8 = match parts[1].parse() {
-                        Ok(f) => f,
-                        Err(_) => continue,
-                    };
-                    let direction = match parts[2] {
-                        "u" => ElevatorDirection::UP,
-                        "d" => ElevatorDirection::DOWN,
-                        _ => continue,
-                    };
-                    let request = ElevatorRequest::new(direction, floor);
-                    input_tx.send(UserCommand::HallCall(request)).unwrap();
+        match parts[0] {
+            "h" => {
+                if parts.len() != 3 {
+                    println!("Usage: h <floor> <u|d>");
+                    continue;
                 }
-                "c" => {
-                    if parts.len() != 2 {
-                        println!("Usage: c <floor>");
-                        continue;
-                    }
-                    let floor: i8 = match parts[1].parse() {
-                        Ok(f) => f,
-                        Err(_) => continue,
-                    };
-                    input_tx.send(UserCommand::CarCall(floor)).unwrap();
-                }
-                "q" => {
-                    input_tx.send(UserCommand::Quit).unwrap();
-                    break;
+                let floor: i8 = match parts[1].parse() {
+                    Ok(f) => f,
+                    Err(_) => continue,
+                };
+                let direction = match parts[2] {
+                    "u" => ElevatorDirection::UP,
+                    "d" => ElevatorDirection::DOWN,
+                    _ => continue,
+                };
+                let request = ElevatorRequest::new(direction, floor);
+                input_tx.send(UserCommand::HallCall(request, None)).unwrap();
+            }
+            "c" => {
+                if parts.len() != 2 {
+                    println!("Usage: c <floor>");
+                    continue;
                 }
-                _ => {
-                    println!("Unknown command");
+                let floor: i8 = match parts[1].parse() {
+                    Ok(f) => f,
+                    Err(_) => continue,
+                };
+                input_tx.send(UserCommand::CarCall(floor, None)).unwrap();
+            }
+            "q" => {
+                input_tx.send(UserCommand::Quit).unwrap();
+                break;
+            }
+            _ => {
+                println!("Unknown command");
+            }
+        }
+    }
+}
+
+fn read_rpc_commands(input_tx: mpsc::Sender<UserCommand>) {
+    for line in std::io::stdin().lines() {
+        let line = line.unwrap();
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let reply = ui::handle_rpc_line(&line, &input_tx);
+        println!("{}", reply.response);
+
+        if reply.quit {
+            break;
+        }
+    }
+}
+
+struct CliOptions {
+    rpc_mode: bool,
+    units: DisplayUnits,
+    log_format: LogFormat,
+}
+
+fn parse_args(args: &[String]) -> Result<CliOptions, String> {
+    let mut options = CliOptions {
+        rpc_mode: false,
+        units: DisplayUnits::Metric,
+        log_format: LogFormat::Text,
+    };
+
+    let mut args = args.iter().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--rpc" => options.rpc_mode = true,
+            "--imperial" => options.units = DisplayUnits::Imperial,
+            "--log-format" => {
+                options.log_format = match args.next().map(String::as_str) {
+                    Some("text") => LogFormat::Text,
+                    Some("json") => LogFormat::Json,
+                    Some(other) => return Err(format!("unknown log format: {}", other)),
+                    None => return Err("--log-format needs a value: text or json".to_string()),
                 }
             }
+            _ => return Err(format!("unknown argument: {}", arg)),
+        }
+    }
+
+    Ok(options)
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let CliOptions {
+        rpc_mode,
+        units,
+        log_format,
+    } = match parse_args(&args) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(2);
         }
+    };
+
+    setup_logger(rpc_mode, log_format).expect("failed");
+    if !rpc_mode {
+        print!("\x1B[2J\x1B[1;1H");
+        println!("elevator-sim.");
+    }
+    let mut indicator_sink = ConsoleIndicatorSink;
+
+    let physics = ElevatorPhysics::builder()
+        .mass(100.0)
+        .car_half_extents(1.0, 3.0)
+        .initial_position(3.10)
+        .gravity(-9.81)
+        .motor_constant(100.0)
+        .build();
+
+    let mut simulation = Simulation::new(Elevator::new(), physics, |encoder, motor| {
+        let mut elevator_controller = ElevatorPIDFFController::new(
+            encoder,
+            motor,
+            12.0,
+            PIDController::new(5.0, 0.0, 20.0),
+            FeedForward::new(10.0, 0.0, 1.0),
+            5.0,
+            0.1,
+        );
+        elevator_controller.set_motion_profile(Some(MotionConstraints {
+            max_velocity: 1.5,
+            max_acceleration: 0.8,
+        }));
+        elevator_controller
+    });
+
+    // get elevator calls using mpsc::channel
+    let (input_tx, input_rx) = mpsc::channel::<UserCommand>();
+
+    let input_thread = thread::spawn(move || match rpc_mode {
+        true => read_rpc_commands(input_tx),
+        false => read_text_commands(input_tx),
     });
 
     let time_step = Duration::from_secs_f32(TIME_STEP);
@@ -113,44 +198,25 @@ fn main() {
     loop {
         // Process user input from UI thread (non-blocking)
         match input_rx.try_recv() {
-            Ok(UserCommand::HallCall(request)) => match elevator.hall_call(request) {
-                Ok(_) => println!("hall call success: {:#?}.", request),
-                Err(e) => println!("hall call error: {:?}. {:#?})", e, request),
-            },
-            Ok(UserCommand::CarCall(floor)) => match elevator.car_call(floor) {
-                Ok(_) => println!("car call success: {}", floor),
-                Err(e) => println!("car call error: {:?}. {}", e, floor),
-            },
-            Ok(UserCommand::Quit) => {
-                println!("shutdown");
-                break;
+            Ok(command) => {
+                if !simulation.submit(command) {
+                    log::info!("shutdown");
+                    break;
+                }
             }
             Err(mpsc::TryRecvError::Disconnected) => {
-                println!("thread disconnected");
+                log::info!("thread disconnected");
                 break;
             }
-            _ => {}
+            Err(mpsc::TryRecvError::Empty) => {}
         }
 
-        // State Loop - decide where to go -> outputs 'target_floor'
-        elevator.state_loop(dt);
-        elevator_controller.set_target_floor(elevator.get_target_floor());
+        simulation.step(dt);
 
-        // Control Loop - decide how to go -> outputs 'voltage'
-        elevator_controller.tick(dt);
-        physics.set_voltage(motor.borrow().get_voltage() as f32);
-
-        // Physics Loop - decide what happened -> outputs 'position'
-        physics.update();
-        encoder
-            .borrow_mut()
-            .set_position(physics.get_position() as f64);
-
-        if let Some(floor) = elevator_controller.get_current_floor() {
-            let _ = elevator.notify_reached_floor(floor);
+        if !rpc_mode {
+            ui::log_to_terminal(&simulation.display_data(), units);
+            indicator_sink.show(&Indicators::from_elevator(simulation.get_elevator()));
         }
-
-        ui::log_to_terminal(&elevator, &elevator_controller, &physics, motor.borrow());
         thread::sleep(time_step);
     }
 
@@ -0,0 +1,195 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{ElevatorController, ElevatorPhysics, Encoder, SimulatedEncoder, SimulatedMotor};
+
+const TIME_STEP: f64 = 1.0 / 60.0; // s, one physics update
+const SETTLING_BAND: f64 = 0.05; // m
+
+// target floors over time, each setpoint holds until the next one
+#[derive(PartialEq, Clone, Debug)]
+pub struct TargetProfile {
+    pub setpoints: Vec<(f64, i8)>, // (start time s, floor)
+    pub duration: f64,             // s
+    pub floor_height: f64,         // m
+}
+
+impl TargetProfile {
+    fn get_target_floor(&self, time: f64) -> i8 {
+        self.setpoints
+            .iter()
+            .take_while(|(start, _)| *start <= time)
+            .last()
+            .map_or(0, |(_, floor)| *floor)
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct TrackingMetrics {
+    pub rms_error: f64,             // m
+    pub peak_overshoot: f64,        // m, furthest past a target in the direction of approach
+    pub settling_time: Option<f64>, // s after the last setpoint, None if it never settled
+    pub control_effort: f64,        // V^2 s, integral of the squared motor voltage
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct ComparisonReport {
+    pub a: TrackingMetrics,
+    pub b: TrackingMetrics,
+}
+
+// each controller gets its own plant, built from the same defaults
+pub fn compare_controllers<A, B>(
+    profile: &TargetProfile,
+    build_a: impl FnOnce(Rc<RefCell<SimulatedEncoder>>, Rc<RefCell<SimulatedMotor>>) -> A,
+    build_b: impl FnOnce(Rc<RefCell<SimulatedEncoder>>, Rc<RefCell<SimulatedMotor>>) -> B,
+) -> ComparisonReport
+where
+    A: ElevatorController,
+    B: ElevatorController,
+{
+    ComparisonReport {
+        a: track_profile(profile, build_a),
+        b: track_profile(profile, build_b),
+    }
+}
+
+fn track_profile<C: ElevatorController>(
+    profile: &TargetProfile,
+    build: impl FnOnce(Rc<RefCell<SimulatedEncoder>>, Rc<RefCell<SimulatedMotor>>) -> C,
+) -> TrackingMetrics {
+    let mut physics = ElevatorPhysics::builder()
+        .timestep(TIME_STEP as f32)
+        .build();
+    let encoder = Rc::new(RefCell::new(SimulatedEncoder::new(
+        physics.get_position() as f64
+    )));
+    let motor = Rc::new(RefCell::new(SimulatedMotor::new()));
+    let mut controller = build(Rc::clone(&encoder), Rc::clone(&motor));
+
+    let last_change = profile
+        .setpoints
+        .iter()
+        .map(|(start, _)| *start)
+        .fold(0.0, f64::max);
+
+    let mut squared_error = 0.0;
+    let mut control_effort = 0.0;
+    let mut peak_overshoot: f64 = 0.0;
+    let mut settled_since = None;
+    let mut approach = 0.0;
+    let mut target_floor = None;
+
+    let steps = (profile.duration / TIME_STEP).round() as usize;
+    for step in 0..steps {
+        let time = step as f64 * TIME_STEP;
+        let floor = profile.get_target_floor(time);
+        let target = floor as f64 * profile.floor_height;
+
+        if target_floor != Some(floor) {
+            target_floor = Some(floor);
+            approach = (target - physics.get_position() as f64).signum();
+            controller.set_target_floor(floor);
+        }
+
+        controller.tick(TIME_STEP);
+        let voltage = motor.borrow().get_voltage();
+        control_effort += voltage * voltage * TIME_STEP;
+        physics.set_voltage(voltage as f32);
+        physics.update();
+        encoder
+            .borrow_mut()
+            .set_position(physics.get_position() as f64);
+        encoder
+            .borrow_mut()
+            .set_velocity(physics.get_velocity() as f64);
+
+        let error = target - encoder.borrow().get_position();
+        squared_error += error * error;
+        peak_overshoot = peak_overshoot.max(-error * approach);
+
+        settled_since = match error.abs() < SETTLING_BAND {
+            true if time >= last_change => settled_since.or(Some(time)),
+            _ => None,
+        };
+    }
+
+    TrackingMetrics {
+        rms_error: (squared_error / steps.max(1) as f64).sqrt(),
+        peak_overshoot,
+        settling_time: settled_since.map(|since: f64| since - last_change),
+        control_effort,
+    }
+}
+
+#[cfg(test)]
+mod comparison_tests {
+    use super::*;
+    use crate::{ElevatorPIDFFController, FeedForward, PIDController};
+
+    fn pid_controller<'a>(
+        pid: PIDController,
+    ) -> impl FnOnce(
+        Rc<RefCell<SimulatedEncoder>>,
+        Rc<RefCell<SimulatedMotor>>,
+    ) -> ElevatorPIDFFController<'a> {
+        move |encoder, motor| {
+            ElevatorPIDFFController::new(
+                encoder,
+                motor,
+                12.0,
+                pid,
+                FeedForward::new(9.81, 0.0, 0.0),
+                5.0,
+                0.1,
+            )
+        }
+    }
+
+    #[test]
+    fn test_damping_reduces_overshoot() {
+        let profile = TargetProfile {
+            setpoints: vec![(0.0, 0), (1.0, 2)],
+            duration: 30.0,
+            floor_height: 5.0,
+        };
+
+        let report = compare_controllers(
+            &profile,
+            pid_controller(PIDController::new(5.0, 0.0, 20.0)),
+            pid_controller(PIDController::new(5.0, 0.0, 2.0)),
+        );
+
+        assert!(
+            report.a.peak_overshoot < report.b.peak_overshoot,
+            "more damping should overshoot less: {:?}",
+            report
+        );
+        assert!(
+            report.a.settling_time.is_some(),
+            "damped tuning should settle: {:?}",
+            report
+        );
+        assert_ne!(report.a, report.b);
+    }
+
+    #[test]
+    fn test_high_gain_costs_more_effort() {
+        let profile = TargetProfile {
+            setpoints: vec![(0.0, 0), (1.0, 1), (10.0, 0)],
+            duration: 20.0,
+            floor_height: 5.0,
+        };
+
+        let report = compare_controllers(
+            &profile,
+            pid_controller(PIDController::new(40.0, 0.0, 60.0)),
+            pid_controller(PIDController::new(2.0, 0.0, 8.0)),
+        );
+
+        assert!(
+            report.a.control_effort > report.b.control_effort,
+            "aggressive tuning should spend more effort: {:?}",
+            report
+        );
+    }
+}
@@ -0,0 +1,272 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::{
+    CallResult, DisplayData, Elevator, ElevatorController, ElevatorPIDFFController,
+    ElevatorPhysics, ElevatorRequest, Encoder, Sensors, SimulatedEncoder, SimulatedMotor,
+    UserCommand, answer_call, collect_display_data,
+};
+
+// the whole loop, logic -> controller -> physics -> encoder, on simulated time only.
+// nothing here sleeps or reads input, so tests can step it as fast as they like
+pub struct Simulation {
+    elevator: Elevator,
+    controller: ElevatorPIDFFController<'static>,
+    physics: ElevatorPhysics,
+    encoder: Rc<RefCell<SimulatedEncoder>>,
+    motor: Rc<RefCell<SimulatedMotor>>,
+    sensors: Sensors<'static>,
+    time: f64,
+}
+
+impl Simulation {
+    // the controller is built over the simulation's own encoder and motor
+    pub fn new(
+        elevator: Elevator,
+        physics: ElevatorPhysics,
+        build_controller: impl FnOnce(
+            Rc<RefCell<SimulatedEncoder>>,
+            Rc<RefCell<SimulatedMotor>>,
+        ) -> ElevatorPIDFFController<'static>,
+    ) -> Self {
+        let encoder = Rc::new(RefCell::new(SimulatedEncoder::new(
+            physics.get_position() as f64
+        )));
+        let motor = Rc::new(RefCell::new(SimulatedMotor::new()));
+        let controller = build_controller(Rc::clone(&encoder), Rc::clone(&motor));
+        let sensors = Sensors::new(Rc::clone(&encoder));
+
+        Simulation {
+            elevator,
+            controller,
+            physics,
+            encoder,
+            motor,
+            sensors,
+            time: 0.0,
+        }
+    }
+
+    pub fn hall_call(&mut self, request: ElevatorRequest) -> CallResult {
+        self.elevator.hall_call(request)
+    }
+
+    pub fn car_call(&mut self, floor: i8) -> CallResult {
+        self.elevator.car_call(floor)
+    }
+
+    // applies a frontend command, returns false once the frontend asked to quit
+    pub fn submit(&mut self, command: UserCommand) -> bool {
+        match command {
+            UserCommand::HallCall(request, reply) => {
+                match answer_call(reply, self.hall_call(request)) {
+                    Ok(_) => log::info!("hall call success: {:?}.", request),
+                    Err(e) => log::warn!("hall call error: {:?}. {:?}", e, request),
+                }
+            }
+            UserCommand::CarCall(floor, reply) => match answer_call(reply, self.car_call(floor)) {
+                Ok(_) => log::info!("car call success: {}", floor),
+                Err(e) => log::warn!("car call error: {:?}. {}", e, floor),
+            },
+            UserCommand::GetState(reply) => {
+                let _ = reply.send(self.display_data());
+            }
+            UserCommand::Quit => return false,
+        }
+
+        true
+    }
+
+    // a step that advances no time is skipped, the controller and profile divide by dt
+    pub fn step(&mut self, dt: f64) {
+        if !dt.is_finite() || dt <= 0.0 {
+            log::warn!("ignoring simulation step of {}s", dt);
+            return;
+        }
+        if let Err(e) = self.physics.set_timestep(dt as f32) {
+            log::warn!("ignoring simulation step: {:?}", e);
+            return;
+        }
+
+        // State Loop - decide where to go -> outputs 'target_floor'
+        self.elevator.state_loop(dt);
+        self.controller
+            .set_target_floor(self.elevator.get_target_floor());
+
+        // Control Loop - decide how to go -> outputs 'voltage'
+        self.controller.tick(dt);
+        self.physics
+            .set_voltage(self.motor.borrow().get_voltage() as f32);
+
+        // Physics Loop - decide what happened -> outputs 'position'
+        self.physics.update();
+        self.encoder
+            .borrow_mut()
+            .set_position(self.physics.get_sensed_position() as f64);
+        self.encoder
+            .borrow_mut()
+            .set_velocity(self.physics.get_velocity() as f64);
+
+        if let Some(floor) = self.controller.get_current_floor()
+            && let Err(e) = self.elevator.notify_reached_floor(floor)
+            && floor != self.elevator.get_current_floor()
+        {
+            log::warn!(
+                "floor notification dropped: {:?}. controller at {}, logic at {}",
+                e,
+                floor,
+                self.elevator.get_current_floor()
+            );
+            self.elevator.force_set_current_floor(floor);
+        }
+
+        self.time += dt;
+    }
+
+    pub fn display_data(&self) -> DisplayData {
+        collect_display_data(
+            &self.elevator,
+            &self.controller,
+            &self.physics,
+            self.motor.borrow(),
+            &self.sensors,
+        )
+    }
+
+    // simulated seconds since the start
+    pub fn get_time(&self) -> f64 {
+        self.time
+    }
+
+    pub fn get_elevator(&self) -> &Elevator {
+        &self.elevator
+    }
+
+    pub fn get_mut_elevator(&mut self) -> &mut Elevator {
+        &mut self.elevator
+    }
+
+    pub fn get_controller(&self) -> &ElevatorPIDFFController<'static> {
+        &self.controller
+    }
+
+    pub fn get_mut_controller(&mut self) -> &mut ElevatorPIDFFController<'static> {
+        &mut self.controller
+    }
+
+    pub fn get_physics(&self) -> &ElevatorPhysics {
+        &self.physics
+    }
+
+    pub fn get_mut_physics(&mut self) -> &mut ElevatorPhysics {
+        &mut self.physics
+    }
+}
+
+#[cfg(test)]
+mod headless_tests {
+    use super::*;
+    use crate::{ElevatorDirection, ElevatorState, FeedForward, MotionConstraints, PIDController};
+    use std::sync::mpsc;
+
+    const DT: f64 = 1.0 / 60.0;
+
+    fn build_simulation() -> Simulation {
+        let physics = ElevatorPhysics::builder()
+            .mass(100.0)
+            .car_half_extents(1.0, 3.0)
+            .motor_constant(100.0)
+            .build();
+
+        Simulation::new(Elevator::new(), physics, |encoder, motor| {
+            let mut controller = ElevatorPIDFFController::new(
+                encoder,
+                motor,
+                12.0,
+                PIDController::new(5.0, 0.0, 20.0),
+                FeedForward::new(9.81, 0.0, 1.0),
+                5.0,
+                0.1,
+            );
+            controller.set_motion_profile(Some(MotionConstraints {
+                max_velocity: 1.5,
+                max_acceleration: 0.8,
+            }));
+            controller
+        })
+    }
+
+    #[test]
+    fn test_car_reaches_requested_floor() {
+        let mut simulation = build_simulation();
+        assert_eq!(simulation.car_call(3), Ok(true));
+
+        let mut arrived_at = None;
+        while simulation.get_time() < 30.0 {
+            simulation.step(DT);
+            if arrived_at.is_none()
+                && matches!(
+                    simulation.get_elevator().get_state(),
+                    ElevatorState::WAITING(_, _)
+                )
+            {
+                arrived_at = Some(simulation.get_time());
+            }
+        }
+
+        let arrived_at = arrived_at.expect("car should stop at floor 3 within 30s");
+        assert!(arrived_at < 20.0, "15m should take well under 20s");
+        assert_eq!(simulation.get_elevator().get_current_floor(), 3);
+        assert_eq!(*simulation.get_elevator().get_state(), ElevatorState::IDLE);
+        assert!(
+            (simulation.get_physics().get_position() - 15.0).abs() < 0.1,
+            "car should be level with floor 3, at {}",
+            simulation.get_physics().get_position()
+        );
+    }
+
+    #[test]
+    fn test_steps_without_time_are_ignored() {
+        let mut simulation = build_simulation();
+        assert_eq!(simulation.car_call(1), Ok(true));
+        simulation.step(DT);
+
+        for dt in [0.0, -DT, f64::NAN, f64::INFINITY] {
+            simulation.step(dt);
+        }
+        assert!(
+            !simulation.get_controller().is_faulted(),
+            "a zero step must not fault the controller"
+        );
+        assert_eq!(simulation.get_time(), DT);
+
+        while simulation.get_time() < 20.0 {
+            simulation.step(DT);
+        }
+        assert_eq!(simulation.get_elevator().get_current_floor(), 1);
+        assert_eq!(*simulation.get_elevator().get_state(), ElevatorState::IDLE);
+    }
+
+    #[test]
+    fn test_commands_are_applied() {
+        let mut simulation = build_simulation();
+
+        let (reply_tx, reply_rx) = mpsc::channel();
+        assert!(simulation.submit(UserCommand::HallCall(
+            ElevatorRequest::new(ElevatorDirection::DOWN, 2),
+            Some(reply_tx),
+        )));
+        assert_eq!(reply_rx.recv().unwrap(), Ok(true));
+
+        for _ in 0..60 {
+            simulation.step(DT);
+        }
+        let (state_tx, state_rx) = mpsc::channel();
+        assert!(simulation.submit(UserCommand::GetState(state_tx)));
+        let display_data = state_rx.recv().unwrap();
+        assert_eq!(display_data.logic_target_floor, 2);
+        assert!(display_data.velocity > 0.0, "car should be on its way up");
+
+        assert!(!simulation.submit(UserCommand::Quit));
+    }
+}
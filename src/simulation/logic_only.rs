@@ -0,0 +1,80 @@
+use crate::{Elevator, ElevatorState};
+
+const TICK: f64 = 1.0; // s, whole seconds keep the dwell timer exact
+const MAX_TICKS: usize = 10_000;
+
+// the Elevator without controller or physics, the car advances one floor per tick
+pub struct LogicOnlySimulation {
+    elevator: Elevator,
+}
+
+impl LogicOnlySimulation {
+    pub fn new(elevator: Elevator) -> Self {
+        Self { elevator }
+    }
+
+    pub fn get_elevator(&self) -> &Elevator {
+        &self.elevator
+    }
+
+    pub fn get_mut_elevator(&mut self) -> &mut Elevator {
+        &mut self.elevator
+    }
+
+    // one floor of travel, then one state_loop
+    pub fn tick(&mut self) {
+        self.elevator.advance_one_floor();
+        self.elevator.state_loop(TICK);
+    }
+
+    // runs until the car is idle with nothing queued, returns the floors it stopped at
+    pub fn run_to_idle(&mut self) -> Vec<i8> {
+        let mut stops = Vec::new();
+        let mut was_waiting = false;
+
+        for _ in 0..MAX_TICKS {
+            if *self.elevator.get_state() == ElevatorState::IDLE
+                && self.elevator.get_all_requests().next().is_none()
+            {
+                break;
+            }
+
+            self.tick();
+
+            let is_waiting = matches!(self.elevator.get_state(), ElevatorState::WAITING(_, _));
+            if is_waiting && !was_waiting {
+                stops.push(self.elevator.get_current_floor());
+            }
+            was_waiting = is_waiting;
+        }
+
+        stops
+    }
+}
+
+#[cfg(test)]
+mod logic_only_tests {
+    use super::*;
+    use crate::{ElevatorDirection, ElevatorRequest};
+
+    #[test]
+    fn test_visit_order_is_exact() {
+        let mut simulation = LogicOnlySimulation::new(Elevator::new());
+        let elevator = simulation.get_mut_elevator();
+        assert_eq!(elevator.car_call(5), Ok(true));
+        assert_eq!(elevator.car_call(2), Ok(true));
+        assert_eq!(elevator.car_call(8), Ok(true));
+        assert_eq!(
+            elevator.hall_call(ElevatorRequest::new(ElevatorDirection::DOWN, 3)),
+            Ok(true)
+        );
+
+        assert_eq!(
+            simulation.run_to_idle(),
+            vec![2, 5, 8, 3],
+            "up through the car calls, then down to the hall call"
+        );
+        assert_eq!(simulation.get_elevator().get_current_floor(), 3);
+        assert!(simulation.run_to_idle().is_empty(), "nothing left to serve");
+    }
+}
@@ -1,5 +1,11 @@
+mod comparison;
+mod headless;
+mod logic_only;
 mod physics;
 mod simulated_hardware;
 
+pub use comparison::*;
+pub use headless::*;
+pub use logic_only::*;
 pub use physics::*;
 pub use simulated_hardware::*;
@@ -1,5 +1,39 @@
 use rapier2d::prelude::*;
 
+const STOPPED_SPEED: f32 = 0.01; // m/s
+const CRUISE_ACCELERATION: f32 = 0.05; // m/s^2
+const BRAKE_MAX_FORCE: f32 = 3000.0; // N
+const BRAKE_ENGAGE_TIME: f32 = 0.2; // s
+const SLAB_HALF_THICKNESS: f32 = 0.1; // m, pit floor and overhead slab
+const COMMUTATION_PERIOD: f32 = 1.0 / 15.0; // s, period of the motor's position ripple
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum MotionPhase {
+    Accelerating,
+    Cruising,
+    Decelerating,
+    Stopped,
+}
+
+impl MotionPhase {
+    pub fn from_motion(velocity: f32, acceleration: f32) -> Self {
+        let accelerating = acceleration.abs() >= CRUISE_ACCELERATION;
+
+        if velocity.abs() < STOPPED_SPEED {
+            return match accelerating {
+                true => MotionPhase::Accelerating,
+                false => MotionPhase::Stopped,
+            };
+        }
+
+        match accelerating {
+            false => MotionPhase::Cruising,
+            true if velocity.signum() == acceleration.signum() => MotionPhase::Accelerating,
+            true => MotionPhase::Decelerating,
+        }
+    }
+}
+
 pub struct ElevatorPhysics {
     // Rapier specific components
     rigid_body_set: RigidBodySet,
@@ -15,33 +49,187 @@ pub struct ElevatorPhysics {
     ccd_solver: CCDSolver,
     query_pipeline: QueryPipeline,
     elevator_body_handle: RigidBodyHandle,
+    elevator_collider_handle: ColliderHandle,
+    shaft_collider_handles: Vec<ColliderHandle>,
 
     // Config
-    motor_constant: f32, // N/V (force per volt)
+    mass: f32,            // kg
+    car_half_height: f32, // m, offset from the body center to the car floor
+    motor_constant: f32,  // N/V (force per volt)
+    frame_dt: f32,        // s, time advanced by one update()
+    substeps: u32,
+    max_force_rate: Option<f32>, // N/s, limits how fast the motor force can change
+    cable: Option<(f32, f32)>,   // N/m and N*s/m, stiffness and damping of an elastic cable
+    cable_anchor: f32,           // m, car floor position where the cable is unstretched
+    brake_max_force: f32,        // N
+    brake_engage_time: f32,      // s, from released to full holding force
+    shaft_height: Option<f32>,   // m, highest car floor position
+    position_ripple: f32,        // m/V, sensed position ripple per volt applied
 
     // State variables
-    voltage: f32, // V Current voltage to be applied
+    voltage: f32,           // V Current voltage to be applied
+    disturbance_force: f32, // N External vertical force, e.g. wind or stack effect
+    applied_force: f32,     // N Motor force applied on the last update
+    acceleration: f32,      // m/s^2 measured over the last update
+    brake_engaged: bool,
+    brake_engagement: f32, // 0..1, share of the holding force available
+    brake_force: f32,      // N Brake force applied on the last update
+    elapsed: f32,          // s, simulated time, phase of the ripple
 }
 
-impl ElevatorPhysics {
-    pub fn new(
-        mass: f32,
-        translation_x: f32,
-        translation_y: f32,
-        initial_y_position: f32,
-        gravity_y: f32,
-        motor_constant: f32,
-    ) -> Self {
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum ElevatorPhysicsError {
+    InvalidMass(f32),
+    InvalidGravity(f32),
+    InvalidCarSize(f32, f32),
+    InvalidShaftHeight(f32),
+    InvalidTimestep(f32),
+    InvalidCable(f32, f32),
+}
+
+// named alternative to the positional ElevatorPhysics::new
+#[derive(Clone, Copy, Debug)]
+pub struct ElevatorPhysicsBuilder {
+    mass: f32,                    // kg
+    car_half_extents: (f32, f32), // m
+    initial_position: f32,        // m, y of the car body center
+    gravity: f32,                 // m/s^2
+    motor_constant: f32,          // N/V
+    timestep: f32,                // s
+    substeps: u32,
+    max_force_rate: Option<f32>, // N/s
+    cable: Option<(f32, f32)>,   // N/m and N*s/m, None keeps the cable rigid
+    shaft_height: Option<f32>,   // m, highest car floor position, None leaves the top open
+}
+
+impl Default for ElevatorPhysicsBuilder {
+    fn default() -> Self {
+        Self {
+            mass: 100.0,
+            car_half_extents: (1.0, 3.0),
+            initial_position: 3.10,
+            gravity: -9.81,
+            motor_constant: 100.0,
+            timestep: 1.0 / 60.0,
+            substeps: 1,
+            max_force_rate: None,
+            cable: None,
+            shaft_height: None,
+        }
+    }
+}
+
+impl ElevatorPhysicsBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn mass(mut self, mass: f32) -> Self {
+        self.mass = mass;
+        self
+    }
+
+    pub fn car_half_extents(mut self, half_width: f32, half_height: f32) -> Self {
+        self.car_half_extents = (half_width, half_height);
+        self
+    }
+
+    pub fn initial_position(mut self, initial_position: f32) -> Self {
+        self.initial_position = initial_position;
+        self
+    }
+
+    pub fn gravity(mut self, gravity: f32) -> Self {
+        self.gravity = gravity;
+        self
+    }
+
+    pub fn motor_constant(mut self, motor_constant: f32) -> Self {
+        self.motor_constant = motor_constant;
+        self
+    }
+
+    pub fn timestep(mut self, timestep: f32) -> Self {
+        self.timestep = timestep;
+        self
+    }
+
+    pub fn substeps(mut self, substeps: u32) -> Self {
+        self.substeps = substeps;
+        self
+    }
+
+    pub fn max_force_rate(mut self, max_force_rate: f32) -> Self {
+        self.max_force_rate = Some(max_force_rate);
+        self
+    }
+
+    // the car hangs on a spring-damper anchored where it starts, evaluated on every substep
+    pub fn cable(mut self, stiffness: f32, damping: f32) -> Self {
+        self.cable = Some((stiffness, damping));
+        self
+    }
+
+    pub fn shaft_height(mut self, shaft_height: f32) -> Self {
+        self.shaft_height = Some(shaft_height);
+        self
+    }
+
+    pub fn build(self) -> ElevatorPhysics {
+        self.try_build()
+            .unwrap_or_else(|error| panic!("invalid elevator physics: {:?}", error))
+    }
+
+    pub fn try_build(self) -> Result<ElevatorPhysics, ElevatorPhysicsError> {
+        let (half_width, half_height) = self.car_half_extents;
+
+        if !is_positive(self.mass) {
+            return Err(ElevatorPhysicsError::InvalidMass(self.mass));
+        }
+        if !self.gravity.is_finite() {
+            return Err(ElevatorPhysicsError::InvalidGravity(self.gravity));
+        }
+        if !is_positive(half_width) || !is_positive(half_height) {
+            return Err(ElevatorPhysicsError::InvalidCarSize(
+                half_width,
+                half_height,
+            ));
+        }
+        if !is_positive(self.timestep) {
+            return Err(ElevatorPhysicsError::InvalidTimestep(self.timestep));
+        }
+        if let Some(shaft_height) = self.shaft_height
+            && !is_positive(shaft_height)
+        {
+            return Err(ElevatorPhysicsError::InvalidShaftHeight(shaft_height));
+        }
+        if let Some((stiffness, damping)) = self.cable
+            && !(is_positive(stiffness) && damping.is_finite() && damping >= 0.0)
+        {
+            return Err(ElevatorPhysicsError::InvalidCable(stiffness, damping));
+        }
+
+        Ok(self.build_unchecked())
+    }
+
+    fn build_unchecked(self) -> ElevatorPhysics {
+        let (translation_x, translation_y) = self.car_half_extents;
+
         let mut rigid_body_set = RigidBodySet::new();
         let mut collider_set = ColliderSet::new();
 
-        // ground
-        let ground_collider = ColliderBuilder::cuboid(100.0, 0.1).build();
-        collider_set.insert(ground_collider);
+        let shaft_collider_handles = insert_shaft_colliders(
+            &mut collider_set,
+            translation_x,
+            translation_y,
+            self.shaft_height,
+        );
 
-        let gravity_vector = vector![0.0, gravity_y];
-        // todo: set the time step
-        let integration_parameters = IntegrationParameters::default();
+        let gravity_vector = vector![0.0, self.gravity];
+        let integration_parameters = IntegrationParameters {
+            dt: self.timestep,
+            ..IntegrationParameters::default()
+        };
         let physics_pipeline = PhysicsPipeline::new();
         let island_manager = IslandManager::new();
         let broad_phase = DefaultBroadPhase::new();
@@ -51,22 +239,32 @@ impl ElevatorPhysics {
         let ccd_solver = CCDSolver::new();
         let query_pipeline = QueryPipeline::new();
 
+        // a car placed into the pit floor would be pushed out on the first step
+        let lowest_position = translation_y;
+        if self.initial_position < lowest_position {
+            log::warn!(
+                "car center {} is inside the pit floor, starting at {}",
+                self.initial_position,
+                lowest_position
+            );
+        }
+
         // Create the elevator rigid body
         let elevator_rigid_body = RigidBodyBuilder::dynamic()
-            .translation(vector![0.0, initial_y_position])
+            .translation(vector![0.0, self.initial_position.max(lowest_position)])
             .lock_rotations()
             .build();
         let elevator_collider = ColliderBuilder::cuboid(translation_x, translation_y)
-            .mass(mass)
+            .mass(self.mass)
             .build();
         let elevator_body_handle = rigid_body_set.insert(elevator_rigid_body);
-        collider_set.insert_with_parent(
+        let elevator_collider_handle = collider_set.insert_with_parent(
             elevator_collider,
             elevator_body_handle,
             &mut rigid_body_set,
         );
 
-        Self {
+        ElevatorPhysics {
             rigid_body_set,
             collider_set,
             gravity_vector,
@@ -80,33 +278,224 @@ impl ElevatorPhysics {
             ccd_solver,
             query_pipeline,
             elevator_body_handle,
-            motor_constant,
+            elevator_collider_handle,
+            shaft_collider_handles,
+            mass: self.mass,
+            car_half_height: translation_y,
+            motor_constant: self.motor_constant,
+            frame_dt: self.timestep,
+            substeps: self.substeps.max(1),
+            max_force_rate: self.max_force_rate,
+            cable: self.cable,
+            cable_anchor: self.initial_position.max(lowest_position) - translation_y,
+            brake_max_force: BRAKE_MAX_FORCE,
+            brake_engage_time: BRAKE_ENGAGE_TIME,
+            shaft_height: self.shaft_height,
+            position_ripple: 0.0,
             voltage: 0.0,
+            disturbance_force: 0.0,
+            applied_force: 0.0,
+            acceleration: 0.0,
+            brake_engaged: false,
+            brake_engagement: 0.0,
+            brake_force: 0.0,
+            elapsed: 0.0,
         }
     }
+}
+
+fn is_positive(value: f32) -> bool {
+    value.is_finite() && value > 0.0
+}
+
+// pit floor with its top at height 0 like floor 0, and the overhead slab that stops the
+// car floor at `shaft_height`. both are as wide as the car
+fn insert_shaft_colliders(
+    collider_set: &mut ColliderSet,
+    half_width: f32,
+    half_height: f32,
+    shaft_height: Option<f32>,
+) -> Vec<ColliderHandle> {
+    let mut handles = Vec::new();
+
+    let ground_collider = ColliderBuilder::cuboid(half_width, SLAB_HALF_THICKNESS)
+        .translation(vector![0.0, -SLAB_HALF_THICKNESS])
+        .build();
+    handles.push(collider_set.insert(ground_collider));
+
+    if let Some(shaft_height) = shaft_height {
+        let overhead_collider = ColliderBuilder::cuboid(half_width, SLAB_HALF_THICKNESS)
+            .translation(vector![
+                0.0,
+                shaft_height + 2.0 * half_height + SLAB_HALF_THICKNESS
+            ])
+            .build();
+        handles.push(collider_set.insert(overhead_collider));
+    }
+
+    handles
+}
+
+impl ElevatorPhysics {
+    pub fn new(
+        mass: f32,
+        translation_x: f32,
+        translation_y: f32,
+        initial_y_position: f32,
+        gravity_y: f32,
+        motor_constant: f32,
+    ) -> Self {
+        Self::try_new(
+            mass,
+            translation_x,
+            translation_y,
+            initial_y_position,
+            gravity_y,
+            motor_constant,
+        )
+        .unwrap_or_else(|error| panic!("invalid elevator physics: {:?}", error))
+    }
+
+    pub fn try_new(
+        mass: f32,
+        translation_x: f32,
+        translation_y: f32,
+        initial_y_position: f32,
+        gravity_y: f32,
+        motor_constant: f32,
+    ) -> Result<Self, ElevatorPhysicsError> {
+        ElevatorPhysicsBuilder::new()
+            .mass(mass)
+            .car_half_extents(translation_x, translation_y)
+            .initial_position(initial_y_position)
+            .gravity(gravity_y)
+            .motor_constant(motor_constant)
+            .try_build()
+    }
+
+    pub fn builder() -> ElevatorPhysicsBuilder {
+        ElevatorPhysicsBuilder::new()
+    }
 
     pub fn update(&mut self) {
-        let motor_force_y = self.voltage * self.motor_constant;
-
-        let elevator_body = self.get_mut_elevator_body();
-        elevator_body.reset_forces(true);
-        elevator_body.add_force(vector![0.0, motor_force_y], true);
-
-        self.physics_pipeline.step(
-            &self.gravity_vector,
-            &self.integration_parameters,
-            &mut self.island_manager,
-            &mut self.broad_phase,
-            &mut self.narrow_phase,
-            &mut self.rigid_body_set,
-            &mut self.collider_set,
-            &mut self.impulse_joint_set,
-            &mut self.multibody_joint_set,
-            &mut self.ccd_solver,
-            Some(&mut self.query_pipeline),
-            &(),
-            &(),
-        );
+        let commanded_force = self.voltage * self.motor_constant;
+        let motor_force_y = match self.max_force_rate {
+            Some(rate) => {
+                let max_delta = rate * self.frame_dt;
+                self.applied_force
+                    + (commanded_force - self.applied_force).clamp(-max_delta, max_delta)
+            }
+            None => commanded_force,
+        };
+        self.applied_force = motor_force_y;
+        let previous_velocity = self.get_velocity();
+        let disturbance_force_y = self.disturbance_force;
+
+        self.brake_engagement = match self.brake_engaged {
+            true => (self.brake_engagement + self.frame_dt / self.brake_engage_time).min(1.0),
+            false => 0.0,
+        };
+        // static friction: cancels the other forces and the current motion, up to its capacity
+        let brake_capacity = self.brake_engagement * self.brake_max_force;
+        let weight_y = self.mass * self.gravity_vector.y;
+        let required_force = -(motor_force_y + disturbance_force_y + weight_y)
+            - self.mass * previous_velocity / self.frame_dt;
+        let brake_force_y = required_force.clamp(-brake_capacity, brake_capacity);
+        self.brake_force = brake_force_y;
+
+        self.integration_parameters.dt = self.frame_dt / self.substeps as f32;
+        for _ in 0..self.substeps {
+            let cable_force_y = self.get_cable_force();
+            let elevator_body = self.get_mut_elevator_body();
+            elevator_body.reset_forces(true);
+            elevator_body.add_force(vector![0.0, motor_force_y], true);
+            elevator_body.add_force(vector![0.0, disturbance_force_y], true);
+            elevator_body.add_force(vector![0.0, brake_force_y], true);
+            elevator_body.add_force(vector![0.0, cable_force_y], true);
+
+            self.physics_pipeline.step(
+                &self.gravity_vector,
+                &self.integration_parameters,
+                &mut self.island_manager,
+                &mut self.broad_phase,
+                &mut self.narrow_phase,
+                &mut self.rigid_body_set,
+                &mut self.collider_set,
+                &mut self.impulse_joint_set,
+                &mut self.multibody_joint_set,
+                &mut self.ccd_solver,
+                Some(&mut self.query_pipeline),
+                &(),
+                &(),
+            );
+        }
+
+        self.acceleration = (self.get_velocity() - previous_velocity) / self.frame_dt;
+        self.elapsed += self.frame_dt;
+    }
+
+    // the stiffer the cable, the more substeps it takes to stay stable
+    fn get_cable_force(&self) -> f32 {
+        match self.cable {
+            Some((stiffness, damping)) => {
+                -stiffness * (self.get_position() - self.cable_anchor)
+                    - damping * self.get_velocity()
+            }
+            None => 0.0,
+        }
+    }
+
+    // time advanced by one update(), keep it in step with the controller's dt
+    pub fn set_timestep(&mut self, timestep: f32) -> Result<(), ElevatorPhysicsError> {
+        if !is_positive(timestep) {
+            return Err(ElevatorPhysicsError::InvalidTimestep(timestep));
+        }
+
+        self.frame_dt = timestep;
+        Ok(())
+    }
+
+    pub fn get_timestep(&self) -> f32 {
+        self.frame_dt
+    }
+
+    // split each update() into `substeps` smaller pipeline steps
+    pub fn set_substeps(&mut self, substeps: u32) {
+        self.substeps = substeps.max(1);
+    }
+
+    pub fn get_substeps(&self) -> u32 {
+        self.substeps
+    }
+
+    pub fn set_max_force_rate(&mut self, max_force_rate: Option<f32>) {
+        self.max_force_rate = max_force_rate;
+    }
+
+    pub fn set_brake_engaged(&mut self, engaged: bool) {
+        self.brake_engaged = engaged;
+    }
+
+    pub fn set_brake_parameters(&mut self, max_force: f32, engage_time: f32) {
+        self.brake_max_force = max_force;
+        self.brake_engage_time = engage_time.max(f32::EPSILON);
+    }
+
+    pub fn get_brake_force(&self) -> f32 {
+        self.brake_force
+    }
+
+    pub fn set_disturbance_force(&mut self, force: f32) {
+        self.disturbance_force = force;
+    }
+
+    pub fn get_applied_force(&self) -> f32 {
+        self.applied_force
+    }
+
+    // voltage that balances the car's weight, e.g. to calibrate FeedForward::kg
+    pub fn holding_voltage(&self) -> f32 {
+        -self.mass * self.gravity_vector.y / self.motor_constant
     }
 
     pub fn set_voltage(&mut self, voltage: f32) {
@@ -114,13 +503,90 @@ impl ElevatorPhysics {
     }
 
     pub fn get_position(&self) -> f32 {
-        self.get_elevator_body().translation().y - 3.0
+        self.get_elevator_body().translation().y - self.car_half_height
+    }
+
+    // commutation couples the motor voltage into the sensed position, 0 disables it
+    pub fn set_position_ripple(&mut self, amplitude: f32) {
+        self.position_ripple = amplitude;
+    }
+
+    // position as an encoder on the car would read it
+    pub fn get_sensed_position(&self) -> f32 {
+        let phase = std::f32::consts::TAU * self.elapsed / COMMUTATION_PERIOD;
+        self.get_position() + self.position_ripple * self.voltage * phase.sin()
+    }
+
+    // free travel below the car floor and above the car roof, the top is unbounded without a shaft height
+    pub fn clearance(&self) -> (f32, f32) {
+        let position = self.get_position();
+        let above = match self.shaft_height {
+            Some(shaft_height) => shaft_height - position,
+            None => f32::INFINITY,
+        };
+        (position, above)
+    }
+
+    // rebuilds the car and shaft colliders, the car floor stays where it is and
+    // shaft_height still bounds it
+    pub fn set_car_size(
+        &mut self,
+        half_width: f32,
+        half_height: f32,
+    ) -> Result<(), ElevatorPhysicsError> {
+        if !is_positive(half_width) || !is_positive(half_height) {
+            return Err(ElevatorPhysicsError::InvalidCarSize(
+                half_width,
+                half_height,
+            ));
+        }
+
+        for handle in std::mem::take(&mut self.shaft_collider_handles)
+            .into_iter()
+            .chain([self.elevator_collider_handle])
+        {
+            self.collider_set.remove(
+                handle,
+                &mut self.island_manager,
+                &mut self.rigid_body_set,
+                true,
+            );
+        }
+        self.shaft_collider_handles = insert_shaft_colliders(
+            &mut self.collider_set,
+            half_width,
+            half_height,
+            self.shaft_height,
+        );
+
+        let collider = ColliderBuilder::cuboid(half_width, half_height)
+            .mass(self.mass)
+            .build();
+        self.elevator_collider_handle = self.collider_set.insert_with_parent(
+            collider,
+            self.elevator_body_handle,
+            &mut self.rigid_body_set,
+        );
+
+        let position = self.get_position();
+        self.car_half_height = half_height;
+        self.get_mut_elevator_body()
+            .set_translation(vector![0.0, position + half_height], true);
+        Ok(())
     }
 
     pub fn get_velocity(&self) -> f32 {
         self.get_elevator_body().linvel().y
     }
 
+    pub fn get_acceleration(&self) -> f32 {
+        self.acceleration
+    }
+
+    pub fn motion_phase(&self) -> MotionPhase {
+        MotionPhase::from_motion(self.get_velocity(), self.acceleration)
+    }
+
     fn get_elevator_body(&self) -> &RigidBody {
         self.rigid_body_set
             .get(self.elevator_body_handle)
@@ -133,3 +599,625 @@ impl ElevatorPhysics {
             .expect("elevator body not found")
     }
 }
+
+// sinusoidal disturbance force at simulation time `time`, e.g. for stack-effect tests
+pub fn sinusoidal_disturbance(amplitude: f32, period: f32, time: f32) -> f32 {
+    amplitude * (std::f32::consts::TAU * time / period).sin()
+}
+
+#[cfg(test)]
+mod physics_tests {
+    use super::*;
+    use crate::{
+        ElevatorController, ElevatorPIDFFController, Encoder, FeedForward, PIDController,
+        SimulatedEncoder, SimulatedMotor,
+    };
+    use std::{cell::RefCell, rc::Rc};
+
+    fn displacement_after(substeps: u32, updates: usize) -> f32 {
+        let mut physics = ElevatorPhysics::new(100.0, 1.0, 3.0, 3.10, -9.81, 100.0);
+        physics.set_substeps(substeps);
+        physics.set_voltage(15.0);
+
+        let start = physics.get_position();
+        for _ in 0..updates {
+            physics.update();
+        }
+        physics.get_position() - start
+    }
+
+    #[test]
+    fn test_substeps_advance_the_same_time() {
+        let single = displacement_after(1, 60);
+        let substepped = displacement_after(8, 60);
+
+        // net force 1500N - 981N on 100kg over one second
+        let expected = 0.5 * (15.0 * 100.0 - 100.0 * 9.81) / 100.0;
+
+        assert!(
+            (substepped - expected).abs() < 0.05,
+            "substepped displacement {} should match {}",
+            substepped,
+            expected
+        );
+        assert!(
+            (single - substepped).abs() < 0.05,
+            "substeps should not change the simulated duration ({} vs {})",
+            single,
+            substepped
+        );
+    }
+
+    // largest distance from the cable anchor over ten seconds. stops at the first metre,
+    // rapier panics once a diverging body leaves its broad phase bounds
+    fn cable_excursion(substeps: u32) -> f32 {
+        let mut physics = ElevatorPhysics::builder()
+            .initial_position(20.0)
+            .timestep(0.05)
+            .substeps(substeps)
+            .cable(1.0e5, 600.0)
+            .build();
+        let anchor = physics.get_position();
+
+        let mut excursion: f32 = 0.0;
+        for _ in 0..200 {
+            physics.update();
+            excursion = excursion.max((physics.get_position() - anchor).abs());
+            if excursion > 1.0 {
+                break;
+            }
+        }
+        excursion
+    }
+
+    #[test]
+    fn test_substeps_keep_a_stiff_cable_stable() {
+        let single = cable_excursion(1);
+        let substepped = cable_excursion(8);
+
+        assert!(
+            single > 1.0,
+            "one 50ms step per update should diverge, moved {}",
+            single
+        );
+        // the static stretch is 100kg * 9.81 / 1e5 N/m, about 1cm
+        assert!(
+            substepped < 0.05,
+            "8 substeps should stay near the anchor, moved {}",
+            substepped
+        );
+    }
+
+    #[test]
+    fn test_timestep_sets_the_simulated_duration() {
+        // net force 1500N - 981N on 100kg over one second
+        let expected = 0.5 * (15.0 * 100.0 - 100.0 * 9.81) / 100.0;
+
+        for updates in [30, 100, 250] {
+            let mut physics = ElevatorPhysics::builder().build();
+            assert_eq!(physics.set_timestep(1.0 / updates as f32), Ok(()));
+            physics.set_voltage(15.0);
+
+            let start = physics.get_position();
+            for _ in 0..updates {
+                physics.update();
+            }
+            let displacement = physics.get_position() - start;
+            assert!(
+                (displacement - expected).abs() < 0.05,
+                "{} updates over 1s moved {}, expected {}",
+                updates,
+                displacement,
+                expected
+            );
+        }
+
+        let built = ElevatorPhysics::builder().timestep(0.01).build();
+        assert_eq!(built.get_timestep(), 0.01);
+        assert_eq!(
+            ElevatorPhysics::builder().timestep(0.0).try_build().err(),
+            Some(ElevatorPhysicsError::InvalidTimestep(0.0))
+        );
+
+        // the runtime path rejects the same input and keeps the previous timestep
+        let mut physics = ElevatorPhysics::builder().timestep(0.01).build();
+        assert_eq!(
+            physics.set_timestep(-0.5),
+            Err(ElevatorPhysicsError::InvalidTimestep(-0.5))
+        );
+        assert_eq!(physics.get_timestep(), 0.01);
+    }
+
+    #[test]
+    fn test_force_ramp_limits_voltage_step() {
+        let mut physics = ElevatorPhysics::new(100.0, 1.0, 3.0, 3.10, -9.81, 100.0);
+        // 1500N step, limited to 300N per 1/60s update
+        physics.set_max_force_rate(Some(300.0 * 60.0));
+        physics.set_voltage(15.0);
+
+        let mut forces = Vec::new();
+        for _ in 0..7 {
+            physics.update();
+            forces.push(physics.get_applied_force());
+        }
+
+        let expected = [300.0, 600.0, 900.0, 1200.0, 1500.0, 1500.0, 1500.0];
+        for (force, expected) in forces.iter().zip(expected) {
+            assert!(
+                (force - expected).abs() < 1e-2,
+                "ramped force {:?} should follow {:?}",
+                forces,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_force_ramp_off_by_default() {
+        let mut physics = ElevatorPhysics::new(100.0, 1.0, 3.0, 3.10, -9.81, 100.0);
+        physics.set_voltage(15.0);
+        physics.update();
+
+        assert_eq!(physics.get_applied_force(), 1500.0);
+    }
+
+    #[test]
+    fn test_motion_phase_from_motion() {
+        assert_eq!(MotionPhase::from_motion(0.0, 0.0), MotionPhase::Stopped);
+        assert_eq!(
+            MotionPhase::from_motion(0.0, 1.0),
+            MotionPhase::Accelerating
+        );
+        assert_eq!(
+            MotionPhase::from_motion(1.5, 0.8),
+            MotionPhase::Accelerating
+        );
+        assert_eq!(
+            MotionPhase::from_motion(-1.5, -0.8),
+            MotionPhase::Accelerating
+        );
+        assert_eq!(MotionPhase::from_motion(1.5, 0.0), MotionPhase::Cruising);
+        assert_eq!(MotionPhase::from_motion(-1.5, 0.01), MotionPhase::Cruising);
+        assert_eq!(
+            MotionPhase::from_motion(1.5, -0.8),
+            MotionPhase::Decelerating
+        );
+        assert_eq!(
+            MotionPhase::from_motion(-1.5, 0.8),
+            MotionPhase::Decelerating
+        );
+    }
+
+    #[test]
+    fn test_motion_phase_while_climbing() {
+        let mut physics = ElevatorPhysics::new(100.0, 1.0, 3.0, 3.10, -9.81, 100.0);
+        assert_eq!(physics.motion_phase(), MotionPhase::Stopped);
+
+        physics.set_voltage(15.0);
+        for _ in 0..10 {
+            physics.update();
+        }
+        assert_eq!(physics.motion_phase(), MotionPhase::Accelerating);
+
+        // exactly the holding voltage: no net force
+        physics.set_voltage(9.81);
+        physics.update();
+        physics.update();
+        assert_eq!(physics.motion_phase(), MotionPhase::Cruising);
+
+        physics.set_voltage(5.0);
+        physics.update();
+        assert_eq!(physics.motion_phase(), MotionPhase::Decelerating);
+    }
+
+    #[test]
+    fn test_holding_voltage_keeps_car_still() {
+        let mut physics = ElevatorPhysics::builder()
+            .mass(250.0)
+            .motor_constant(80.0)
+            .build();
+        assert!((physics.holding_voltage() - 250.0 * 9.81 / 80.0).abs() < 1e-4);
+
+        physics.set_voltage(physics.holding_voltage());
+        for _ in 0..600 {
+            physics.update();
+            assert!(
+                physics.get_velocity().abs() < 1e-3,
+                "car should not drift, moving at {}",
+                physics.get_velocity()
+            );
+        }
+    }
+
+    #[test]
+    fn test_shaft_bounds_the_car() {
+        let mut physics = ElevatorPhysics::builder()
+            .car_half_extents(0.5, 1.0)
+            .initial_position(1.1)
+            .shaft_height(6.0)
+            .build();
+
+        physics.set_voltage(-20.0);
+        for _ in 0..120 {
+            physics.update();
+        }
+        assert!(
+            physics.get_position() > -0.02,
+            "car should rest on the pit floor, at {}",
+            physics.get_position()
+        );
+
+        physics.set_voltage(30.0);
+        for _ in 0..240 {
+            physics.update();
+        }
+        assert!(
+            (physics.get_position() - 6.0).abs() < 0.02,
+            "car should stop at the top of the shaft, at {}",
+            physics.get_position()
+        );
+
+        assert_eq!(
+            ElevatorPhysics::builder()
+                .shaft_height(-1.0)
+                .try_build()
+                .err(),
+            Some(ElevatorPhysicsError::InvalidShaftHeight(-1.0))
+        );
+    }
+
+    #[test]
+    fn test_clearance_tracks_the_car() {
+        let mut physics = ElevatorPhysics::builder()
+            .car_half_extents(0.5, 1.0)
+            .initial_position(1.0)
+            .shaft_height(6.0)
+            .build();
+        let shaft_length = 6.0 + 2.0;
+
+        let (below, above) = physics.clearance();
+        assert!(below.abs() < 1e-3, "car starts on the pit floor, {}", below);
+        assert!((below + above + 2.0 - shaft_length).abs() < 1e-3);
+
+        physics.set_voltage(15.0);
+        for _ in 0..30 {
+            physics.update();
+        }
+        let (moved_below, moved_above) = physics.clearance();
+        assert!(moved_below > below, "clearance below should grow going up");
+        assert!(
+            moved_above < above,
+            "clearance above should shrink going up"
+        );
+        assert!(
+            (moved_below + moved_above + 2.0 - shaft_length).abs() < 1e-3,
+            "clearances {} and {} should fill the shaft",
+            moved_below,
+            moved_above
+        );
+
+        let open = ElevatorPhysics::builder().build();
+        assert_eq!(open.clearance().1, f32::INFINITY);
+    }
+
+    #[test]
+    fn test_position_ripple_follows_holding_voltage() {
+        let ripple_over = |mass: f32, amplitude: f32| {
+            let mut physics = ElevatorPhysics::builder().mass(mass).build();
+            physics.set_position_ripple(amplitude);
+            physics.set_voltage(physics.holding_voltage());
+
+            let mut peak: f32 = 0.0;
+            for _ in 0..60 {
+                physics.update();
+                peak = peak.max((physics.get_sensed_position() - physics.get_position()).abs());
+            }
+            (peak, physics.holding_voltage())
+        };
+
+        let (still, _) = ripple_over(100.0, 0.0);
+        assert_eq!(still, 0.0, "no ripple by default");
+
+        let (light, light_voltage) = ripple_over(100.0, 1e-4);
+        let (heavy, heavy_voltage) = ripple_over(300.0, 1e-4);
+        assert!(
+            (light - 1e-4 * light_voltage).abs() < 1e-5,
+            "ripple {} should reach the amplitude times {}V",
+            light,
+            light_voltage
+        );
+        assert!(
+            (heavy / light - heavy_voltage / light_voltage).abs() < 0.05,
+            "ripple should scale with the holding voltage ({} vs {})",
+            heavy,
+            light
+        );
+    }
+
+    #[test]
+    fn test_controller_rejects_constant_disturbance() {
+        let encoder = Rc::new(RefCell::new(SimulatedEncoder::new(0.0)));
+        let motor = Rc::new(RefCell::new(SimulatedMotor::new()));
+        let mut physics = ElevatorPhysics::new(100.0, 1.0, 3.0, 3.10, -9.81, 100.0);
+        physics.set_disturbance_force(-50.0);
+
+        let mut controller = ElevatorPIDFFController::new(
+            Rc::clone(&encoder),
+            Rc::clone(&motor),
+            12.0,
+            PIDController::new(5.0, 0.0, 20.0),
+            FeedForward::new(10.0, 0.0, 0.0),
+            5.0,
+            0.1,
+        );
+        controller.set_target_floor(1);
+
+        let dt = 1.0 / 60.0;
+        for _ in 0..(60 * 60) {
+            controller.tick(dt);
+            physics.set_voltage(motor.borrow().get_voltage() as f32);
+            physics.update();
+            encoder
+                .borrow_mut()
+                .set_position(physics.get_position() as f64);
+        }
+
+        assert!(
+            (physics.get_position() - 5.0).abs() < 0.1,
+            "car should hold floor 1 against the disturbance, at {}",
+            physics.get_position()
+        );
+    }
+
+    #[test]
+    fn test_sinusoidal_disturbance() {
+        assert_eq!(sinusoidal_disturbance(50.0, 4.0, 0.0), 0.0);
+        assert!((sinusoidal_disturbance(50.0, 4.0, 1.0) - 50.0).abs() < 1e-3);
+        assert!((sinusoidal_disturbance(50.0, 4.0, 3.0) + 50.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_zero_substeps_is_clamped() {
+        let mut physics = ElevatorPhysics::new(100.0, 1.0, 3.0, 3.10, -9.81, 100.0);
+        physics.set_substeps(0);
+        assert_eq!(physics.get_substeps(), 1);
+    }
+
+    #[test]
+    fn test_car_starts_free_of_the_ground() {
+        let mut physics = ElevatorPhysics::builder().build();
+        let reference = physics.get_position();
+        assert!((reference - 0.1).abs() < 1e-5);
+
+        for _ in 0..3 {
+            physics.update();
+        }
+
+        // free fall only, no contact force pushing the car
+        let time = 3.0 / 60.0;
+        assert!(
+            (physics.get_velocity() + 9.81 * time).abs() < 1e-3,
+            "car should be falling freely, at {}m/s",
+            physics.get_velocity()
+        );
+        let sag = reference - physics.get_position();
+        assert!(
+            sag > 0.0 && sag < 9.81 * time * time,
+            "car should only sag under gravity, sagged {}",
+            sag
+        );
+
+        let buried = ElevatorPhysics::builder().initial_position(1.0).build();
+        assert_eq!(
+            buried.get_position(),
+            0.0,
+            "car is lifted out of the pit floor"
+        );
+    }
+
+    #[test]
+    fn test_builder_matches_positional_constructor() {
+        let mut positional = ElevatorPhysics::new(120.0, 1.0, 3.0, 3.5, -9.81, 80.0);
+        let mut built = ElevatorPhysics::builder()
+            .motor_constant(80.0)
+            .gravity(-9.81)
+            .initial_position(3.5)
+            .car_half_extents(1.0, 3.0)
+            .mass(120.0)
+            .build();
+
+        assert_eq!(built.get_position(), positional.get_position());
+
+        for physics in [&mut positional, &mut built] {
+            physics.set_voltage(15.0);
+            for _ in 0..30 {
+                physics.update();
+            }
+        }
+
+        assert_eq!(built.get_applied_force(), positional.get_applied_force());
+        assert_eq!(built.get_position(), positional.get_position());
+        assert_eq!(built.get_velocity(), positional.get_velocity());
+    }
+
+    #[test]
+    fn test_car_size_keeps_position_reference() {
+        let mut small = ElevatorPhysics::builder()
+            .car_half_extents(0.8, 1.5)
+            .initial_position(1.5)
+            .build();
+        let mut large = ElevatorPhysics::builder().initial_position(3.0).build();
+        assert_eq!(small.get_position(), 0.0);
+        assert_eq!(large.get_position(), 0.0);
+
+        // resized at runtime, the car floor does not move
+        assert_eq!(large.set_car_size(1.2, 2.0), Ok(()));
+        assert_eq!(large.get_position(), 0.0);
+
+        for physics in [&mut small, &mut large] {
+            for _ in 0..120 {
+                physics.update();
+            }
+        }
+
+        // both cars rest on top of the ground collider
+        assert!(
+            small.get_position().abs() < 0.01,
+            "small car at {}",
+            small.get_position()
+        );
+        assert!(
+            large.get_position().abs() < 0.01,
+            "large car at {}",
+            large.get_position()
+        );
+    }
+
+    #[test]
+    fn test_resized_car_stays_inside_the_shaft() {
+        let mut physics = ElevatorPhysics::builder()
+            .car_half_extents(0.5, 1.0)
+            .initial_position(1.0)
+            .shaft_height(6.0)
+            .build();
+        assert_eq!(physics.set_car_size(0.8, 1.5), Ok(()));
+        assert!((physics.clearance().1 - 6.0).abs() < 1e-3);
+
+        physics.set_voltage(40.0);
+        for _ in 0..240 {
+            physics.update();
+        }
+        assert!(
+            (physics.get_position() - 6.0).abs() < 0.02,
+            "taller car should still stop at the top of the shaft, at {}",
+            physics.get_position()
+        );
+        assert!(physics.clearance().1.abs() < 0.02);
+
+        assert_eq!(
+            physics.set_car_size(0.8, 0.0),
+            Err(ElevatorPhysicsError::InvalidCarSize(0.8, 0.0))
+        );
+        assert!(
+            physics.set_car_size(f32::NAN, 1.0).is_err(),
+            "a NaN width is rejected"
+        );
+    }
+
+    #[test]
+    fn test_brake_handoff_holds_position() {
+        let encoder = Rc::new(RefCell::new(SimulatedEncoder::new(0.0)));
+        let motor = Rc::new(RefCell::new(SimulatedMotor::new()));
+        let mut physics = ElevatorPhysics::builder().initial_position(8.0).build();
+
+        let mut controller = ElevatorPIDFFController::new(
+            Rc::clone(&encoder),
+            Rc::clone(&motor),
+            12.0,
+            PIDController::new(5.0, 0.0, 20.0),
+            FeedForward::new(9.81, 0.0, 0.0),
+            5.0,
+            0.1,
+        );
+        controller.set_target_floor(1);
+        controller.set_brake_handoff_time(0.5);
+
+        let dt = 1.0 / 60.0;
+        let step = |physics: &mut ElevatorPhysics, controller: &mut ElevatorPIDFFController| {
+            encoder
+                .borrow_mut()
+                .set_position(physics.get_position() as f64);
+            controller.tick(dt);
+            physics.set_voltage(motor.borrow().get_voltage() as f32);
+            physics.update();
+        };
+
+        for _ in 0..(60 * 10) {
+            step(&mut physics, &mut controller);
+        }
+        let held_position = physics.get_position();
+        let holding_voltage = motor.borrow().get_voltage();
+        assert!((held_position - 5.0).abs() < 0.1);
+
+        controller.set_brake_engaged(true);
+        physics.set_brake_engaged(true);
+
+        let mut voltages = Vec::new();
+        for _ in 0..(60 * 2) {
+            step(&mut physics, &mut controller);
+            voltages.push(motor.borrow().get_voltage());
+            assert!(
+                (physics.get_position() - held_position).abs() < 0.02,
+                "car should not sag during the handoff, at {}",
+                physics.get_position()
+            );
+        }
+
+        let midway = voltages[14];
+        assert!(
+            midway > 0.3 * holding_voltage && midway < 0.7 * holding_voltage,
+            "voltage {} should be ramping down from {}",
+            midway,
+            holding_voltage
+        );
+        assert_eq!(voltages[30], 0.0, "voltage reaches zero after the handoff");
+        assert_eq!(*voltages.last().unwrap(), 0.0);
+        assert!(physics.get_brake_force() > 900.0, "brake holds the car");
+    }
+
+    #[test]
+    fn test_invalid_setups_are_rejected() {
+        let error = |result: Result<ElevatorPhysics, ElevatorPhysicsError>| result.err();
+
+        assert_eq!(
+            error(ElevatorPhysics::try_new(0.0, 1.0, 3.0, 3.1, -9.81, 100.0)),
+            Some(ElevatorPhysicsError::InvalidMass(0.0))
+        );
+        assert_eq!(
+            error(ElevatorPhysics::try_new(-5.0, 1.0, 3.0, 3.1, -9.81, 100.0)),
+            Some(ElevatorPhysicsError::InvalidMass(-5.0))
+        );
+        assert_eq!(
+            error(ElevatorPhysics::try_new(
+                100.0,
+                1.0,
+                3.0,
+                3.1,
+                f32::NAN,
+                100.0
+            ))
+            .map(|e| { matches!(e, ElevatorPhysicsError::InvalidGravity(g) if g.is_nan()) }),
+            Some(true)
+        );
+        assert_eq!(
+            error(ElevatorPhysics::try_new(
+                100.0,
+                1.0,
+                3.0,
+                3.1,
+                f32::INFINITY,
+                100.0
+            )),
+            Some(ElevatorPhysicsError::InvalidGravity(f32::INFINITY))
+        );
+        assert_eq!(
+            error(ElevatorPhysics::try_new(100.0, 0.0, 3.0, 3.1, -9.81, 100.0)),
+            Some(ElevatorPhysicsError::InvalidCarSize(0.0, 3.0))
+        );
+        assert_eq!(
+            error(
+                ElevatorPhysics::builder()
+                    .car_half_extents(1.0, -2.0)
+                    .try_build()
+            ),
+            Some(ElevatorPhysicsError::InvalidCarSize(1.0, -2.0))
+        );
+        assert!(ElevatorPhysics::try_new(100.0, 1.0, 3.0, 3.1, -9.81, 100.0).is_ok());
+    }
+
+    #[test]
+    #[should_panic(expected = "InvalidMass")]
+    fn test_new_panics_on_invalid_setup() {
+        ElevatorPhysics::new(0.0, 1.0, 3.0, 3.1, -9.81, 100.0);
+    }
+}
@@ -1,13 +1,29 @@
-use crate::core::{Encoder, Motor};
+use std::collections::VecDeque;
+
+use crate::core::{Encoder, FloorSensor, LoadCell, Motor};
 
 pub struct SimulatedEncoder {
     position: f64,
+    velocity: f64,
+    latency: usize,         // samples between a position update and its reading
+    pending: VecDeque<f64>, // positions not yet visible to get_position
 }
 
 impl SimulatedEncoder {
     pub fn new(initial_position: f64) -> Self {
         Self {
             position: initial_position,
+            velocity: 0.0,
+            latency: 0,
+            pending: VecDeque::new(),
+        }
+    }
+
+    // get_position reports the value from `samples` set_position calls ago
+    pub fn set_latency(&mut self, samples: usize) {
+        self.latency = samples;
+        while self.pending.len() > self.latency {
+            self.position = self.pending.pop_front().unwrap();
         }
     }
 }
@@ -18,12 +34,25 @@ impl Encoder for SimulatedEncoder {
     }
 
     fn set_position(&mut self, position: f64) {
-        self.position = position;
+        self.pending.push_back(position);
+        while self.pending.len() > self.latency {
+            self.position = self.pending.pop_front().unwrap();
+        }
+    }
+
+    fn get_velocity(&self) -> f64 {
+        self.velocity
+    }
+
+    fn set_velocity(&mut self, velocity: f64) {
+        self.velocity = velocity;
     }
 }
 
 pub struct SimulatedMotor {
     voltage: f64,
+    stuck_voltage: Option<f64>, // fault: output ignores commands
+    effectiveness: f64,         // fault: share of the commanded voltage that is applied
 }
 
 impl Default for SimulatedMotor {
@@ -34,11 +63,32 @@ impl Default for SimulatedMotor {
 
 impl SimulatedMotor {
     pub fn new() -> Self {
-        Self { voltage: 0.0 }
+        Self {
+            voltage: 0.0,
+            stuck_voltage: None,
+            effectiveness: 1.0,
+        }
     }
 
+    // applied voltage, including any injected fault
     pub fn get_voltage(&self) -> f64 {
-        self.voltage
+        match self.stuck_voltage {
+            Some(voltage) => voltage,
+            None => self.voltage * self.effectiveness,
+        }
+    }
+
+    pub fn stick_voltage(&mut self, voltage: f64) {
+        self.stuck_voltage = Some(voltage);
+    }
+
+    pub fn set_effectiveness(&mut self, fraction: f64) {
+        self.effectiveness = fraction;
+    }
+
+    pub fn clear_faults(&mut self) {
+        self.stuck_voltage = None;
+        self.effectiveness = 1.0;
     }
 }
 
@@ -47,3 +97,105 @@ impl Motor for SimulatedMotor {
         self.voltage = voltage;
     }
 }
+
+pub struct SimulatedLoadCell {
+    load: f64,
+}
+
+impl SimulatedLoadCell {
+    pub fn new(initial_load: f64) -> Self {
+        Self { load: initial_load }
+    }
+
+    pub fn set_load(&mut self, load: f64) {
+        self.load = load;
+    }
+}
+
+impl LoadCell for SimulatedLoadCell {
+    fn get_load(&self) -> f64 {
+        self.load
+    }
+}
+
+#[derive(Default)]
+pub struct SimulatedFloorSensor {
+    floor: Option<i8>,
+}
+
+impl SimulatedFloorSensor {
+    pub fn new() -> Self {
+        Self { floor: None }
+    }
+
+    pub fn set_floor(&mut self, floor: Option<i8>) {
+        self.floor = floor;
+    }
+}
+
+impl FloorSensor for SimulatedFloorSensor {
+    fn get_floor(&self) -> Option<i8> {
+        self.floor
+    }
+}
+
+#[cfg(test)]
+mod simulated_hardware_tests {
+    use super::*;
+    use crate::ElevatorPhysics;
+
+    fn acceleration_with(motor: &mut SimulatedMotor) -> f32 {
+        let mut physics = ElevatorPhysics::builder().gravity(0.0).build();
+        motor.set_voltage(10.0);
+        physics.set_voltage(motor.get_voltage() as f32);
+        physics.update();
+        physics.get_acceleration()
+    }
+
+    #[test]
+    fn test_encoder_latency_lags_by_samples() {
+        let mut encoder = SimulatedEncoder::new(0.0);
+        encoder.set_latency(3);
+
+        for step in 1..=10 {
+            encoder.set_position(step as f64);
+            let expected = (step - 3).max(0) as f64;
+            assert_eq!(
+                encoder.get_position(),
+                expected,
+                "reading after update {} should lag by three",
+                step
+            );
+        }
+
+        encoder.set_latency(0);
+        assert_eq!(encoder.get_position(), 10.0, "dropping latency flushes");
+    }
+
+    #[test]
+    fn test_reduced_effectiveness_halves_acceleration() {
+        let mut motor = SimulatedMotor::new();
+        let healthy = acceleration_with(&mut motor);
+
+        motor.set_effectiveness(0.5);
+        let weak = acceleration_with(&mut motor);
+
+        assert!(
+            (weak - healthy * 0.5).abs() < 1e-3,
+            "{} should be half of {}",
+            weak,
+            healthy
+        );
+    }
+
+    #[test]
+    fn test_stuck_voltage_ignores_commands() {
+        let mut motor = SimulatedMotor::new();
+        motor.stick_voltage(3.0);
+        motor.set_voltage(12.0);
+        assert_eq!(motor.get_voltage(), 3.0);
+
+        motor.clear_faults();
+        assert_eq!(motor.get_voltage(), 12.0);
+    }
+}
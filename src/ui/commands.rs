@@ -0,0 +1,70 @@
+use std::sync::mpsc;
+
+use crate::{DisplayData, ElevatorRequest, ElevatorRequestErr};
+
+pub type CallResult = Result<bool, ElevatorRequestErr>;
+
+pub enum UserCommand {
+    // the optional sender receives the outcome of the call
+    HallCall(ElevatorRequest, Option<mpsc::Sender<CallResult>>),
+    CarCall(i8, Option<mpsc::Sender<CallResult>>),
+    GetState(mpsc::Sender<DisplayData>),
+    Quit,
+}
+
+// forwards a call's outcome to whoever submitted it, a dropped receiver is ignored
+pub fn answer_call(reply: Option<mpsc::Sender<CallResult>>, result: CallResult) -> CallResult {
+    if let Some(reply) = reply {
+        let _ = reply.send(result);
+    }
+    result
+}
+
+#[cfg(test)]
+mod commands_tests {
+    use super::*;
+    use crate::{Elevator, ElevatorDirection};
+
+    #[test]
+    fn test_call_result_is_delivered() {
+        let (commands_tx, commands_rx) = mpsc::channel();
+
+        let frontend = std::thread::spawn(move || {
+            let mut results = Vec::new();
+            for command in [
+                |tx| UserCommand::CarCall(4, Some(tx)),
+                |tx| {
+                    UserCommand::HallCall(ElevatorRequest::new(ElevatorDirection::UP, 2), Some(tx))
+                },
+                |tx| {
+                    UserCommand::HallCall(ElevatorRequest::new(ElevatorDirection::UP, 2), Some(tx))
+                },
+            ] {
+                let (reply_tx, reply_rx) = mpsc::channel();
+                commands_tx.send(command(reply_tx)).unwrap();
+                results.push(reply_rx.recv().unwrap());
+            }
+            results
+        });
+
+        let mut elevator = Elevator::new();
+        elevator.set_service_zone(0..=3);
+        for command in commands_rx {
+            match command {
+                UserCommand::HallCall(request, reply) => {
+                    let _ = answer_call(reply, elevator.hall_call(request));
+                }
+                UserCommand::CarCall(floor, reply) => {
+                    let _ = answer_call(reply, elevator.car_call(floor));
+                }
+                _ => unreachable!(),
+            }
+        }
+
+        assert_eq!(
+            frontend.join().unwrap(),
+            vec![Err(ElevatorRequestErr::OutOfRange), Ok(true), Ok(false),],
+            "each call should get its own outcome back"
+        );
+    }
+}
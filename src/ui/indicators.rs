@@ -0,0 +1,89 @@
+use crate::{Elevator, ElevatorDirection, ElevatorDoorsState, ElevatorState};
+
+// what the hall and car position indicators show
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct Indicators {
+    pub floor: i8,
+    pub direction: Option<ElevatorDirection>,
+    pub doors: ElevatorDoorsState,
+}
+
+impl Indicators {
+    // a waiting car keeps its arrow lit for the direction it will leave in
+    pub fn from_elevator(elevator: &Elevator) -> Self {
+        let (direction, doors) = match elevator.get_state() {
+            ElevatorState::MOVING(direction) => (Some(*direction), ElevatorDoorsState::CLOSED),
+            ElevatorState::WAITING(direction, doors) => (Some(*direction), *doors),
+            ElevatorState::IDLE | ElevatorState::INSPECTION => (None, ElevatorDoorsState::CLOSED),
+        };
+
+        Self {
+            floor: elevator.get_current_floor(),
+            direction,
+            doors,
+        }
+    }
+}
+
+pub trait IndicatorSink {
+    fn show(&mut self, indicators: &Indicators);
+}
+
+pub struct ConsoleIndicatorSink;
+
+impl IndicatorSink for ConsoleIndicatorSink {
+    fn show(&mut self, indicators: &Indicators) {
+        let arrow = match indicators.direction {
+            Some(ElevatorDirection::UP) => "^",
+            Some(ElevatorDirection::DOWN) => "v",
+            None => " ",
+        };
+        println!(
+            "[ {:>3} {} ] doors {:?}",
+            indicators.floor, arrow, indicators.doors
+        );
+    }
+}
+
+#[cfg(test)]
+mod indicators_tests {
+    use super::*;
+
+    #[test]
+    fn test_arrow_follows_motion() {
+        let mut elevator = Elevator::new();
+        elevator.set_seconds_per_floor(Some(1.0));
+        assert_eq!(Indicators::from_elevator(&elevator).direction, None);
+
+        assert_eq!(elevator.car_call(2), Ok(true));
+        elevator.state_loop(0.1);
+        let indicators = Indicators::from_elevator(&elevator);
+        assert_eq!(indicators.direction, Some(ElevatorDirection::UP));
+        assert_eq!(indicators.floor, 0);
+
+        for _ in 0..30 {
+            elevator.state_loop(0.1);
+        }
+        assert!(matches!(elevator.get_state(), ElevatorState::WAITING(_, _)));
+        let indicators = Indicators::from_elevator(&elevator);
+        assert_eq!(indicators.floor, 2);
+        assert_eq!(
+            indicators.direction,
+            Some(ElevatorDirection::UP),
+            "arrival lantern stays lit"
+        );
+
+        assert_eq!(elevator.car_call(0), Ok(true));
+        for _ in 0..150 {
+            elevator.state_loop(0.1);
+            if let ElevatorState::MOVING(direction) = elevator.get_state() {
+                assert_eq!(
+                    Indicators::from_elevator(&elevator).direction,
+                    Some(*direction)
+                );
+            }
+        }
+        assert_eq!(elevator.get_state(), &ElevatorState::IDLE);
+        assert_eq!(Indicators::from_elevator(&elevator).direction, None);
+    }
+}
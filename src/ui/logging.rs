@@ -0,0 +1,55 @@
+use serde_json::{Value, json};
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub enum LogFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+// one JSON object per record, for log aggregators
+pub fn format_json_record(timestamp: &str, record: &log::Record) -> String {
+    let mut object = json!({
+        "timestamp": timestamp,
+        "level": record.level().to_string(),
+        "target": record.target(),
+        "message": record.args().to_string(),
+    });
+
+    if let Some(file) = record.file() {
+        object["file"] = Value::from(file);
+    }
+    if let Some(line) = record.line() {
+        object["line"] = Value::from(line);
+    }
+
+    object.to_string()
+}
+
+#[cfg(test)]
+mod logging_tests {
+    use super::*;
+
+    #[test]
+    fn test_json_record_keys() {
+        // format_args! borrows temporaries, so the record is built inline
+        let line = format_json_record(
+            "2025-01-01 12:00:00",
+            &log::Record::builder()
+                .args(format_args!("hall call success: {}", 3))
+                .level(log::Level::Info)
+                .target("elevator_sim")
+                .line(Some(42))
+                .build(),
+        );
+        let object: Value = serde_json::from_str(&line).unwrap();
+
+        assert_eq!(object["timestamp"], "2025-01-01 12:00:00");
+        assert_eq!(object["level"], "INFO");
+        assert_eq!(object["target"], "elevator_sim");
+        assert_eq!(object["message"], "hall call success: 3");
+        assert_eq!(object["line"], 42);
+        assert!(object.get("file").is_none(), "missing fields are left out");
+        assert!(!line.contains('\n'), "one record per line");
+    }
+}
@@ -1,3 +1,11 @@
+mod commands;
+mod indicators;
+mod logging;
+mod rpc;
 mod terminal_ui;
 
+pub use commands::*;
+pub use indicators::*;
+pub use logging::*;
+pub use rpc::*;
 pub use terminal_ui::*;
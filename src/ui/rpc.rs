@@ -0,0 +1,267 @@
+use std::sync::mpsc;
+
+use serde_json::{Value, json};
+
+use crate::{CallResult, DisplayData, ElevatorDirection, ElevatorRequest, UserCommand};
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const INTERNAL_ERROR: i64 = -32603;
+const CALL_REJECTED: i64 = -32000; // the elevator refused the call, the reason is in `data`
+
+pub struct RpcReply {
+    pub response: String,
+    pub quit: bool,
+}
+
+fn rpc_result(id: Value, result: Value) -> String {
+    json!({ "jsonrpc": "2.0", "result": result, "id": id }).to_string()
+}
+
+fn rpc_error(id: Value, code: i64, message: &str) -> String {
+    json!({
+        "jsonrpc": "2.0",
+        "error": { "code": code, "message": message },
+        "id": id,
+    })
+    .to_string()
+}
+
+fn call_result_to_rpc(id: Value, result: CallResult) -> String {
+    match result {
+        Ok(accepted) => rpc_result(id, json!({ "accepted": accepted })),
+        Err(e) => json!({
+            "jsonrpc": "2.0",
+            "error": {
+                "code": CALL_REJECTED,
+                "message": "call rejected",
+                "data": format!("{:?}", e),
+            },
+            "id": id,
+        })
+        .to_string(),
+    }
+}
+
+// sends a call to the sim loop and waits for its outcome
+fn forward_call(
+    id: Value,
+    commands: &mpsc::Sender<UserCommand>,
+    command: impl FnOnce(mpsc::Sender<CallResult>) -> UserCommand,
+) -> String {
+    let (result_tx, result_rx) = mpsc::channel();
+    if commands.send(command(result_tx)).is_err() {
+        return rpc_error(id, INTERNAL_ERROR, "simulation stopped");
+    }
+
+    match result_rx.recv() {
+        Ok(result) => call_result_to_rpc(id, result),
+        Err(_) => rpc_error(id, INTERNAL_ERROR, "simulation stopped"),
+    }
+}
+
+fn parse_floor(params: &Value) -> Option<i8> {
+    params
+        .get("floor")?
+        .as_i64()
+        .and_then(|floor| i8::try_from(floor).ok())
+}
+
+fn parse_direction(params: &Value) -> Option<ElevatorDirection> {
+    match params.get("direction")?.as_str()? {
+        "up" => Some(ElevatorDirection::UP),
+        "down" => Some(ElevatorDirection::DOWN),
+        _ => None,
+    }
+}
+
+pub fn display_data_to_json(data: &DisplayData) -> Value {
+    json!({
+        "state": data.elevator_state,
+        "current_floor": data.logic_current_floor,
+        "target_floor": data.logic_target_floor,
+        "requests": data.requests,
+        "position": data.position,
+        "velocity": data.velocity,
+    })
+}
+
+// handles one line-delimited JSON-RPC 2.0 request, forwarding calls to the sim loop
+pub fn handle_rpc_line(line: &str, commands: &mpsc::Sender<UserCommand>) -> RpcReply {
+    let reply = |response| RpcReply {
+        response,
+        quit: false,
+    };
+
+    let request: Value = match serde_json::from_str(line) {
+        Ok(request) => request,
+        Err(_) => return reply(rpc_error(Value::Null, PARSE_ERROR, "parse error")),
+    };
+    let id = request.get("id").cloned().unwrap_or(Value::Null);
+    let params = request.get("params").cloned().unwrap_or(Value::Null);
+    let Some(method) = request.get("method").and_then(Value::as_str) else {
+        return reply(rpc_error(id, INVALID_REQUEST, "invalid request"));
+    };
+
+    let command = match method {
+        "hall_call" => {
+            return match (parse_floor(&params), parse_direction(&params)) {
+                (Some(floor), Some(direction)) => {
+                    let request = ElevatorRequest::new(direction, floor);
+                    reply(forward_call(id, commands, |result_tx| {
+                        UserCommand::HallCall(request, Some(result_tx))
+                    }))
+                }
+                _ => reply(rpc_error(
+                    id,
+                    INVALID_PARAMS,
+                    "expected floor and direction",
+                )),
+            };
+        }
+        "car_call" => {
+            return match parse_floor(&params) {
+                Some(floor) => reply(forward_call(id, commands, |result_tx| {
+                    UserCommand::CarCall(floor, Some(result_tx))
+                })),
+                None => reply(rpc_error(id, INVALID_PARAMS, "expected floor")),
+            };
+        }
+        "get_state" => {
+            let (state_tx, state_rx) = mpsc::channel();
+            if commands.send(UserCommand::GetState(state_tx)).is_err() {
+                return reply(rpc_error(id, INTERNAL_ERROR, "simulation stopped"));
+            }
+
+            return match state_rx.recv() {
+                Ok(data) => reply(rpc_result(id, display_data_to_json(&data))),
+                Err(_) => reply(rpc_error(id, INTERNAL_ERROR, "simulation stopped")),
+            };
+        }
+        "quit" => UserCommand::Quit,
+        _ => return reply(rpc_error(id, METHOD_NOT_FOUND, "method not found")),
+    };
+
+    let quit = matches!(command, UserCommand::Quit);
+    match commands.send(command) {
+        Ok(_) => RpcReply {
+            response: rpc_result(id, json!({ "queued": true })),
+            quit,
+        },
+        Err(_) => reply(rpc_error(id, INTERNAL_ERROR, "simulation stopped")),
+    }
+}
+
+#[cfg(test)]
+mod rpc_tests {
+    use super::*;
+
+    use crate::ElevatorRequestErr;
+
+    // stands in for the sim loop, answers each call with the next result
+    fn answer_calls(
+        results: Vec<CallResult>,
+    ) -> (mpsc::Sender<UserCommand>, std::thread::JoinHandle<Vec<i8>>) {
+        let (tx, rx) = mpsc::channel();
+        let sim = std::thread::spawn(move || {
+            let mut floors = Vec::new();
+            for (command, result) in rx.iter().zip(results) {
+                match command {
+                    UserCommand::CarCall(floor, Some(reply))
+                    | UserCommand::HallCall(ElevatorRequest { floor, .. }, Some(reply)) => {
+                        floors.push(floor);
+                        reply.send(result).unwrap();
+                    }
+                    _ => panic!("expected a call with a reply channel"),
+                }
+            }
+            floors
+        });
+        (tx, sim)
+    }
+
+    #[test]
+    fn test_car_call_is_forwarded() {
+        let (tx, sim) = answer_calls(vec![Ok(true)]);
+        let reply = handle_rpc_line(
+            r#"{"jsonrpc": "2.0", "method": "car_call", "params": {"floor": 8}, "id": 7}"#,
+            &tx,
+        );
+
+        let response: Value = serde_json::from_str(&reply.response).unwrap();
+        assert_eq!(response["jsonrpc"], "2.0");
+        assert_eq!(response["id"], 7);
+        assert_eq!(response["result"]["accepted"], true);
+        assert!(!reply.quit);
+
+        drop(tx);
+        assert_eq!(sim.join().unwrap(), vec![8]);
+    }
+
+    #[test]
+    fn test_call_outcome_is_reported() {
+        let (tx, sim) = answer_calls(vec![
+            Ok(false),
+            Err(ElevatorRequestErr::Locked),
+            Err(ElevatorRequestErr::QueueFull),
+        ]);
+        let hall_call = |floor: i8, id: i64| {
+            let line = format!(
+                r#"{{"jsonrpc": "2.0", "method": "hall_call", "params": {{"floor": {}, "direction": "up"}}, "id": {}}}"#,
+                floor, id
+            );
+            serde_json::from_str::<Value>(&handle_rpc_line(&line, &tx).response).unwrap()
+        };
+
+        let response = hall_call(2, 1);
+        assert_eq!(response["result"]["accepted"], false, "already lit");
+
+        let response = hall_call(5, 2);
+        assert_eq!(response["error"]["code"], CALL_REJECTED);
+        assert_eq!(response["error"]["data"], "Locked");
+        assert_eq!(response["id"], 2);
+
+        let response = hall_call(6, 3);
+        assert_eq!(response["error"]["data"], "QueueFull");
+
+        // nobody left to answer
+        let response = hall_call(7, 4);
+        assert_eq!(response["error"]["code"], INTERNAL_ERROR);
+
+        drop(tx);
+        assert_eq!(sim.join().unwrap(), vec![2, 5, 6]);
+    }
+
+    #[test]
+    fn test_errors_are_reported() {
+        let (tx, rx) = mpsc::channel();
+
+        let reply = handle_rpc_line("not json", &tx);
+        let response: Value = serde_json::from_str(&reply.response).unwrap();
+        assert_eq!(response["error"]["code"], PARSE_ERROR);
+
+        let reply = handle_rpc_line(r#"{"jsonrpc": "2.0", "method": "fly", "id": 1}"#, &tx);
+        let response: Value = serde_json::from_str(&reply.response).unwrap();
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+
+        let reply = handle_rpc_line(
+            r#"{"jsonrpc": "2.0", "method": "hall_call", "params": {"floor": 3}, "id": 2}"#,
+            &tx,
+        );
+        let response: Value = serde_json::from_str(&reply.response).unwrap();
+        assert_eq!(response["error"]["code"], INVALID_PARAMS);
+
+        assert!(rx.try_recv().is_err(), "nothing should be enqueued");
+    }
+
+    #[test]
+    fn test_quit_ends_session() {
+        let (tx, rx) = mpsc::channel();
+        let reply = handle_rpc_line(r#"{"jsonrpc": "2.0", "method": "quit", "id": 3}"#, &tx);
+
+        assert!(reply.quit);
+        assert!(matches!(rx.try_recv(), Ok(UserCommand::Quit)));
+    }
+}
@@ -1,23 +1,90 @@
 use crate::{
-    Elevator, ElevatorController, ElevatorPIDFFController, ElevatorPhysics, ElevatorRequest,
-    ElevatorState, SimulatedMotor,
+    DispatchReason, Elevator, ElevatorController, ElevatorPIDFFController, ElevatorPhysics,
+    ElevatorRequest, ElevatorState, MotionPhase, SensorSnapshot, Sensors, SimulatedMotor,
 };
 use std::cell::Ref;
 
+const FEET_PER_METER: f64 = 3.28084;
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub enum DisplayUnits {
+    #[default]
+    Metric,
+    Imperial,
+}
+
+pub fn format_height(meters: f64, units: DisplayUnits) -> String {
+    match units {
+        DisplayUnits::Metric => format!("{:.2}m", meters),
+        DisplayUnits::Imperial => format!("{:.2}ft", meters * FEET_PER_METER),
+    }
+}
+
+pub fn format_velocity(meters_per_second: f64, units: DisplayUnits) -> String {
+    match units {
+        DisplayUnits::Metric => format!("{:.2}m/s", meters_per_second),
+        DisplayUnits::Imperial => format!("{:.2}ft/s", meters_per_second * FEET_PER_METER),
+    }
+}
+
+// position between two floors, `fraction` of the way from `from` to `to`
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct FloorProgress {
+    pub from: i8,
+    pub to: i8,
+    pub fraction: f64,
+}
+
+// the floor last passed is the one behind the direction of travel
+pub fn floor_progress(height: f64, velocity: f64, floor_height: f64) -> FloorProgress {
+    let floors = height / floor_height;
+
+    let from = match velocity < 0.0 {
+        true => floors.ceil(),
+        false => floors.floor(),
+    };
+    let to = match velocity < 0.0 {
+        true => from - 1.0,
+        false => from + 1.0,
+    };
+
+    FloorProgress {
+        from: from as i8,
+        to: to as i8,
+        fraction: (floors - from).abs(),
+    }
+}
+
+pub fn format_floor_progress(progress: &FloorProgress) -> String {
+    format!(
+        "{} -> {} ({:.0}%)",
+        progress.from,
+        progress.to,
+        progress.fraction * 100.0
+    )
+}
+
 pub struct DisplayData {
     pub logic_current_floor: i8,
     pub logic_target_floor: i8,
     pub elevator_state: String,
     pub requests: Vec<String>,
+    pub last_decision: Option<DispatchReason>,
     pub waiting_time: f64,
+    pub dwell_remaining: f64,
 
     pub controller_estimated_current_floor: Option<i8>,
+    pub floor_progress: FloorProgress,
     pub current_height: f64,
+    pub setpoint_height: f64,
     pub target_height: f64,
 
     pub position: f32,
     pub velocity: f32,
+    pub motion_phase: MotionPhase,
     pub motor_voltage: f32,
+
+    pub sensors: SensorSnapshot,
 }
 
 pub fn format_request(request: &ElevatorRequest) -> String {
@@ -28,30 +95,48 @@ pub fn format_elevator_state(state: &ElevatorState) -> String {
     match state {
         ElevatorState::IDLE => "IDLE".to_string(),
         ElevatorState::MOVING(_) => "MOVING".to_string(),
-        ElevatorState::WAITING(_, _) => "WAITING".to_string(),
+        ElevatorState::WAITING(_, doors) => format!("WAITING, doors {:?}", doors),
+        ElevatorState::INSPECTION => "INSPECTION".to_string(),
     }
 }
 
-pub fn log_to_terminal(
+pub fn collect_display_data(
     elevator: &Elevator,
     controller: &ElevatorPIDFFController,
     physics: &ElevatorPhysics,
     motor: Ref<SimulatedMotor>,
-) {
-    let display_data = DisplayData {
+    sensors: &Sensors,
+) -> DisplayData {
+    DisplayData {
         logic_current_floor: elevator.get_current_floor(),
         logic_target_floor: elevator.get_target_floor(),
         elevator_state: format_elevator_state(elevator.get_state()),
-        requests: elevator.get_all_requests().map(format_request).collect(),
+        requests: elevator
+            .get_all_requests_sorted()
+            .iter()
+            .map(format_request)
+            .collect(),
+        last_decision: elevator.get_last_decision(),
         waiting_time: elevator.get_waiting_time(),
+        dwell_remaining: elevator.get_dwell_timer().remaining(),
         controller_estimated_current_floor: controller.get_current_floor(),
+        floor_progress: floor_progress(
+            controller.get_current_height(),
+            sensors.snapshot().velocity,
+            controller.get_floor_height(),
+        ),
         current_height: controller.get_current_height(),
+        setpoint_height: controller.get_setpoint_height(),
         target_height: controller.get_target_height(),
         position: physics.get_position(),
         velocity: physics.get_velocity(),
+        motion_phase: physics.motion_phase(),
         motor_voltage: motor.get_voltage() as f32,
-    };
+        sensors: sensors.snapshot(),
+    }
+}
 
+pub fn log_to_terminal(display_data: &DisplayData, units: DisplayUnits) {
     print!("\x1B[2J\x1B[1;1H");
 
     println!("--- Elevator Logic ---");
@@ -59,6 +144,8 @@ pub fn log_to_terminal(
     println!("Current Floor: {}", display_data.logic_current_floor);
     println!("Target Floor: {}", display_data.logic_target_floor);
     println!("Waiting Time: {:.2}s", display_data.waiting_time);
+    println!("Dwell Remaining: {:.2}s", display_data.dwell_remaining);
+    println!("Last Decision: {:?}", display_data.last_decision);
     println!("Requests:");
     if display_data.requests.is_empty() {
         println!("  No active requests");
@@ -73,15 +160,72 @@ pub fn log_to_terminal(
         "Est. Current Floor: {}",
         match display_data.controller_estimated_current_floor {
             Some(f) => f.to_string(),
-            None => "N/A (between floors)".to_string(),
+            None => format_floor_progress(&display_data.floor_progress),
         }
     );
-    println!("Current Height: {:.2}m", display_data.current_height);
-    println!("Target Height: {:.2}m", display_data.target_height);
+    println!(
+        "Current Height: {}",
+        format_height(display_data.current_height, units)
+    );
+    println!(
+        "Setpoint Height: {}",
+        format_height(display_data.setpoint_height, units)
+    );
+    println!(
+        "Target Height: {}",
+        format_height(display_data.target_height, units)
+    );
 
     println!("\n--- Physics Engine ---");
-    println!("Position: {:.2}m", display_data.position);
-    println!("Velocity: {:.2}m/s", display_data.velocity);
+    println!(
+        "Position: {}",
+        format_height(display_data.position as f64, units)
+    );
+    println!(
+        "Velocity: {}",
+        format_velocity(display_data.velocity as f64, units)
+    );
+    println!("Motion Phase: {:?}", display_data.motion_phase);
     println!("Motor Voltage: {:.2}V", display_data.motor_voltage);
+
+    println!("\n--- Sensors ---");
+    println!(
+        "Encoder Position: {}",
+        format_height(display_data.sensors.position, units)
+    );
+    println!(
+        "Encoder Velocity: {}",
+        format_velocity(display_data.sensors.velocity, units)
+    );
+    if let Some(load) = display_data.sensors.load {
+        println!("Load: {:.1}kg", load);
+    }
+    if let Some(floor) = display_data.sensors.floor {
+        println!("Floor Sensor: {}", floor);
+    }
     println!("----------------------\n");
 }
+
+#[cfg(test)]
+mod terminal_ui_tests {
+    use super::*;
+
+    #[test]
+    fn test_imperial_units() {
+        assert_eq!(format_height(3.0, DisplayUnits::Metric), "3.00m");
+        assert_eq!(format_height(3.0, DisplayUnits::Imperial), "9.84ft");
+        assert_eq!(format_velocity(-1.5, DisplayUnits::Imperial), "-4.92ft/s");
+    }
+
+    #[test]
+    fn test_floor_progress_mid_floor() {
+        let going_up = floor_progress(18.0, 1.2, 5.0);
+        assert_eq!((going_up.from, going_up.to), (3, 4));
+        assert!((going_up.fraction - 0.6).abs() < 1e-9);
+        assert_eq!(format_floor_progress(&going_up), "3 -> 4 (60%)");
+
+        let going_down = floor_progress(18.0, -1.2, 5.0);
+        assert_eq!((going_down.from, going_down.to), (4, 3));
+        assert!((going_down.fraction - 0.4).abs() < 1e-9);
+    }
+}